@@ -0,0 +1,106 @@
+use crate::tokenize::Span;
+use super::{OpCode, Value};
+
+/// A compiled function body: its bytecode, the constants the bytecode
+/// refers to by index, and the source line and span of every byte.
+#[derive(Debug, Clone, Default)]
+pub struct Chunk {
+    code: Vec<u8>,
+    constants: Vec<Value>,
+    // Run-length encoded: each entry is the offset where a run of bytes
+    // compiled from the same token starts, and that token's line and span.
+    lines: Vec<(usize, u32, Option<Span>)>,
+}
+
+impl Chunk {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write(&mut self, byte: u8, line: u32, span: Option<Span>) {
+        if self.lines.last().map(|&(_, last_line, last_span)| (last_line, last_span)) != Some((line, span)) {
+            self.lines.push((self.code.len(), line, span));
+        }
+
+        self.code.push(byte);
+    }
+
+    pub fn write_op(&mut self, op_code: OpCode, line: u32, span: Option<Span>) {
+        self.write(op_code.into(), line, span);
+    }
+
+    /// Overwrites an already written byte, for back-patching jumps.
+    pub fn patch(&mut self, offset: usize, byte: u8) {
+        self.code[offset] = byte;
+    }
+
+    /// Adds `value` to the constants table, returning its index.
+    pub fn add_constant(&mut self, value: Value) -> usize {
+        self.constants.push(value);
+        self.constants.len() - 1
+    }
+
+    /// The index of a constant identical to `value`, if there is one.
+    /// Numbers are compared bit for bit, so `0` and `-0` stay apart.
+    pub fn find_constant(&self, value: Value) -> Option<usize> {
+        self.constants.iter().position(|&constant| match (constant, value) {
+            (Value::Number(a), Value::Number(b)) => a.to_bits() == b.to_bits(),
+            _ => constant == value,
+        })
+    }
+
+    pub fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    pub fn constants(&self) -> &[Value] {
+        &self.constants
+    }
+
+    pub fn len(&self) -> usize {
+        self.code.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty()
+    }
+
+    /// The source line the byte at `offset` was compiled from.
+    pub fn line(&self, offset: usize) -> u32 {
+        self.run(offset).1
+    }
+
+    /// The span of the token the byte at `offset` was compiled from.
+    pub fn span(&self, offset: usize) -> Option<Span> {
+        self.run(offset).2
+    }
+
+    fn run(&self, offset: usize) -> (usize, u32, Option<Span>) {
+        let run = self.lines.partition_point(|&(start, _, _)| start <= offset);
+
+        self.lines[run - 1]
+    }
+}
+
+#[test]
+fn test_chunk_line_table() {
+    use crate::tokenize::Position;
+
+    let span = |line, column| Some(Span::new(Position::new(line, column, 0), Position::new(line, column + 1, 0)));
+
+    let mut chunk = Chunk::new();
+    chunk.write_op(OpCode::Nil, 1, span(1, 1));
+    chunk.write_op(OpCode::Pop, 1, span(1, 1));
+    chunk.write_op(OpCode::True, 3, span(3, 1));
+    chunk.write_op(OpCode::Pop, 3, span(3, 5));
+    chunk.write_op(OpCode::Return, 4, span(4, 1));
+
+    assert_eq!(chunk.len(), 5);
+    assert_eq!(chunk.lines.len(), 4);
+    assert_eq!(
+        (0..chunk.len()).map(|offset| chunk.line(offset)).collect::<Vec<u32>>(),
+        vec![1, 1, 3, 3, 4]
+    );
+    assert_eq!(chunk.span(1), span(1, 1));
+    assert_eq!(chunk.span(3), span(3, 5));
+}
@@ -0,0 +1,46 @@
+use crate::diagnostics::Code;
+use crate::parse::ParseError;
+use crate::resolve::ResolveError;
+
+use std::fmt;
+
+/// An error found while compiling to bytecode. The compiler does the work
+/// of both the parser and the resolver in one pass, and reports the same
+/// errors they do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompileError {
+    Parse(ParseError),
+    Resolve(ResolveError),
+}
+
+impl CompileError {
+    pub fn code(&self) -> &'static Code {
+        match self {
+            CompileError::Parse(parse_error) => parse_error.code(),
+            CompileError::Resolve(resolve_error) => resolve_error.code(),
+        }
+    }
+
+    pub fn line(&self) -> u32 {
+        match self {
+            CompileError::Parse(parse_error) => parse_error.line(),
+            CompileError::Resolve(resolve_error) => resolve_error.line(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            CompileError::Parse(parse_error) => parse_error.message(),
+            CompileError::Resolve(resolve_error) => resolve_error.message(),
+        }
+    }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::Parse(parse_error) => write!(f, "{}", parse_error),
+            CompileError::Resolve(resolve_error) => write!(f, "{}", resolve_error),
+        }
+    }
+}
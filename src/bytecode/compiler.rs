@@ -0,0 +1,1264 @@
+use crate::diagnostics::Code;
+use crate::parse::ParseError;
+use crate::resolve::{LocalLints, ResolveError, ResolveWarning};
+use crate::tokenize::{LiteralType, Span, Token, TokenType};
+use super::{Chunk, CompileError, Heap, ObjFunction, ObjRef, Object, OpCode, Value};
+
+use std::rc::Rc;
+
+type CompileResult<T> = Result<T, ParseError>;
+
+type ParseFn<'src, 'heap> = fn(&mut Compiler<'src, 'heap>, bool) -> CompileResult<()>;
+
+const MAX_ARGUMENTS: usize = 255;
+// Operands that index locals, upvalues and constants are one byte.
+const MAX_SLOTS: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+enum Precedence {
+    None,
+    Assignment,
+    Or,
+    And,
+    Equality,
+    Comparison,
+    Term,
+    Factor,
+    Unary,
+    Call,
+    Primary,
+}
+
+impl Precedence {
+    fn next(self) -> Self {
+        match self {
+            Precedence::None => Precedence::Assignment,
+            Precedence::Assignment => Precedence::Or,
+            Precedence::Or => Precedence::And,
+            Precedence::And => Precedence::Equality,
+            Precedence::Equality => Precedence::Comparison,
+            Precedence::Comparison => Precedence::Term,
+            Precedence::Term => Precedence::Factor,
+            Precedence::Factor => Precedence::Unary,
+            Precedence::Unary => Precedence::Call,
+            Precedence::Call | Precedence::Primary => Precedence::Primary,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FunctionType {
+    Script,
+    Function,
+    Method,
+    // A class's `init` method, which implicitly returns `this`.
+    Initializer,
+}
+
+#[derive(Debug)]
+struct Local {
+    name: String,
+    // `None` while the variable's initializer is being compiled.
+    depth: Option<usize>,
+    is_captured: bool,
+    // `None` for the implicit locals, like `this` and `super`.
+    declaration: Option<Span>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Upvalue {
+    index: u8,
+    // Whether it captures a local of the enclosing function, rather than
+    // one of its upvalues.
+    is_local: bool,
+}
+
+/// The function being compiled, one per level of nesting.
+#[derive(Debug)]
+struct FunctionState {
+    function_type: FunctionType,
+    name: Option<ObjRef>,
+    arity: usize,
+    chunk: Chunk,
+    locals: Vec<Local>,
+    upvalues: Vec<Upvalue>,
+    scope_depth: usize,
+    // Whether "Too many constants in one chunk." was reported already.
+    too_many_constants: bool,
+}
+
+impl FunctionState {
+    fn new(function_type: FunctionType, name: Option<ObjRef>) -> Self {
+        // Slot zero holds the function being called, or `this` in methods.
+        let slot_zero = match function_type {
+            FunctionType::Method | FunctionType::Initializer => "this",
+            FunctionType::Script | FunctionType::Function => "",
+        };
+
+        Self {
+            function_type,
+            name,
+            arity: 0,
+            chunk: Chunk::new(),
+            locals: vec![Local {
+                name: slot_zero.to_string(),
+                depth: Some(0),
+                is_captured: false,
+                declaration: None,
+            }],
+            upvalues: Vec::new(),
+            scope_depth: 0,
+            too_many_constants: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ClassState {
+    has_superclass: bool,
+}
+
+/// Where to roll the compiler back to after a syntax error.
+struct Checkpoint {
+    functions: usize,
+    classes: usize,
+    locals: usize,
+    scope_depth: usize,
+    lints: usize,
+}
+
+/// Single-pass compiler from the tokens produced by `tokenize` straight to
+/// bytecode, without building a syntax tree.
+///
+/// It accepts the same grammar as `Parser` and reports the same errors as
+/// `Parser` and `Resolver` together. As with those two, static errors such
+/// as a misplaced `return` are only reported when there are no syntax
+/// errors.
+#[derive(Debug)]
+pub struct Compiler<'src, 'heap> {
+    tokens: Vec<Token<'src>>,
+    current: usize,
+    heap: &'heap mut Heap,
+
+    functions: Vec<FunctionState>,
+    classes: Vec<ClassState>,
+
+    syntax_errors: Vec<ParseError>,
+    resolve_errors: Vec<ResolveError>,
+    lints: LocalLints,
+}
+
+impl<'src, 'heap> Compiler<'src, 'heap> {
+    /// `Error` tokens are dropped: the lexer already reported them.
+    pub fn new(tokens: Vec<Token<'src>>, heap: &'heap mut Heap) -> Self {
+        let mut tokens: Vec<Token<'src>> = tokens
+            .into_iter()
+            .filter(|token| token.token_type() != &TokenType::Error)
+            .collect();
+
+        if tokens.last().map(|token| token.token_type()) != Some(&TokenType::Eof) {
+            let line = tokens.last().map_or(1, |token| token.line());
+            tokens.push(Token::new(TokenType::Eof, "", None, line));
+        }
+
+        Self {
+            tokens,
+            current: 0,
+            heap,
+
+            functions: vec![FunctionState::new(FunctionType::Script, None)],
+            classes: Vec::new(),
+
+            syntax_errors: Vec::new(),
+            resolve_errors: Vec::new(),
+            lints: LocalLints::new(),
+        }
+    }
+
+    /// Compiles a whole program into the function for its top-level code,
+    /// returned with the warnings in source order.
+    pub fn compile(mut self) -> Result<(ObjRef, Vec<ResolveWarning>), Vec<CompileError>> {
+        while !self.is_at_end() {
+            self.declaration();
+        }
+
+        // The implicit `return` of the script is on the line where input ends.
+        let end = self.peek().clone();
+        let (function, _) = self.end_function(&end);
+
+        if !self.syntax_errors.is_empty() {
+            return Err(self.syntax_errors.into_iter().map(CompileError::Parse).collect());
+        }
+        if !self.resolve_errors.is_empty() {
+            return Err(self.resolve_errors.into_iter().map(CompileError::Resolve).collect());
+        }
+
+        Ok((function, self.lints.into_warnings()))
+    }
+
+    // Declarations and statements.
+
+    fn declaration(&mut self) {
+        let checkpoint = self.checkpoint();
+
+        let result = if self.match_any(&[TokenType::Class]) {
+            self.class_declaration()
+        } else if self.match_any(&[TokenType::Fun]) {
+            self.fun_declaration()
+        } else if self.match_any(&[TokenType::Var]) {
+            self.var_declaration()
+        } else {
+            self.statement()
+        };
+
+        if let Err(parse_error) = result {
+            self.syntax_errors.push(parse_error);
+            self.restore(checkpoint);
+            self.synchronize();
+        }
+    }
+
+    fn class_declaration(&mut self) -> CompileResult<()> {
+        let class_name = self.consume(TokenType::Identifier, "Expect class name.")?.clone();
+        let name_constant = self.identifier_constant(class_name.name());
+        self.declare_variable(&class_name);
+
+        self.emit_op_arg(OpCode::Class, name_constant);
+        self.define_variable(name_constant);
+
+        self.classes.push(ClassState { has_superclass: false });
+
+        if self.match_any(&[TokenType::Less]) {
+            let superclass = self.consume(TokenType::Identifier, "Expect superclass name.")?.clone();
+            self.named_variable(superclass.name(), &superclass, false)?;
+
+            if superclass.name() == class_name.name() {
+                self.resolve_error(&Code::SELF_INHERITANCE, &superclass, "A class can't inherit from itself.");
+            }
+
+            // Methods close over a scope holding `super`.
+            self.begin_scope();
+            self.add_local("super", None);
+            self.define_variable(0);
+
+            self.named_variable(class_name.name(), &class_name, false)?;
+            self.emit_op_at(OpCode::Inherit, &superclass);
+
+            self.classes.last_mut().unwrap().has_superclass = true;
+        }
+
+        // Keep the class on the stack while its methods are added.
+        self.named_variable(class_name.name(), &class_name, false)?;
+
+        self.consume(TokenType::LeftBrace, "Expect '{' before class body.")?;
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            self.method()?;
+        }
+        self.consume(TokenType::RightBrace, "Expect '}' after class body.")?;
+
+        self.emit_op(OpCode::Pop);
+
+        if self.classes.pop().unwrap().has_superclass {
+            self.end_scope();
+        }
+
+        Ok(())
+    }
+
+    fn method(&mut self) -> CompileResult<()> {
+        let name = self.consume(TokenType::Identifier, "Expect method name.")?.clone();
+        let constant = self.identifier_constant(name.name());
+
+        let function_type = match name.name() {
+            "init" => FunctionType::Initializer,
+            _ => FunctionType::Method,
+        };
+        self.function(function_type)?;
+
+        self.emit_op_arg(OpCode::Method, constant);
+
+        Ok(())
+    }
+
+    fn fun_declaration(&mut self) -> CompileResult<()> {
+        let global = self.parse_variable("Expect function name.")?;
+
+        // Initialized straight away, so the body can refer to it.
+        self.mark_initialized();
+        self.function(FunctionType::Function)?;
+        self.define_variable(global);
+
+        Ok(())
+    }
+
+    /// Compiles a function's parameters and body, leaving a closure over it
+    /// on the stack. Its name was just consumed.
+    fn function(&mut self, function_type: FunctionType) -> CompileResult<()> {
+        let kind = match function_type {
+            FunctionType::Method | FunctionType::Initializer => "method",
+            FunctionType::Script | FunctionType::Function => "function",
+        };
+
+        let name = self.previous().name().to_string();
+        let name = self.heap.intern(&name);
+        self.functions.push(FunctionState::new(function_type, Some(name)));
+        self.begin_scope();
+
+        self.consume(TokenType::LeftParen, &format!("Expect '(' after {} name.", kind))?;
+        if !self.check(&TokenType::RightParen) {
+            loop {
+                if self.function_state().arity >= MAX_ARGUMENTS {
+                    let parse_error = self.error(&Code::TOO_MANY_PARAMETERS, self.peek(), "Can't have more than 255 parameters.");
+                    self.syntax_errors.push(parse_error);
+                }
+                self.function_state_mut().arity += 1;
+
+                let constant = self.parse_variable("Expect parameter name.")?;
+                self.define_variable(constant);
+
+                if !self.match_any(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
+
+        self.consume(TokenType::LeftBrace, &format!("Expect '{{' before {} body.", kind))?;
+        self.block()?;
+
+        // No `end_scope`: returning discards the whole frame anyway.
+        let end = self.previous().clone();
+        let (function, upvalues) = self.end_function(&end);
+        self.lints.end_scope();
+
+        let constant = self.make_constant(Value::Obj(function));
+        self.emit_op_arg(OpCode::Closure, constant);
+        for upvalue in upvalues {
+            self.emit_byte(upvalue.is_local as u8);
+            self.emit_byte(upvalue.index);
+        }
+
+        Ok(())
+    }
+
+    fn var_declaration(&mut self) -> CompileResult<()> {
+        let global = self.parse_variable("Expect variable name.")?;
+        self.lints.warn_if_unused();
+
+        if self.match_any(&[TokenType::Equal]) {
+            self.expression()?;
+        } else {
+            self.emit_op(OpCode::Nil);
+        }
+
+        self.consume(TokenType::Semicolon, "Expect ';' after variable declaration.")?;
+        self.define_variable(global);
+
+        Ok(())
+    }
+
+    fn statement(&mut self) -> CompileResult<()> {
+        match self.peek().token_type() {
+            TokenType::For => {
+                self.advance();
+                self.for_statement()
+            }
+            TokenType::If => {
+                self.advance();
+                self.if_statement()
+            }
+            TokenType::Print => {
+                self.advance();
+                self.print_statement()
+            }
+            TokenType::Return => {
+                self.advance();
+                self.return_statement()
+            }
+            TokenType::While => {
+                self.advance();
+                self.while_statement()
+            }
+            TokenType::LeftBrace => {
+                self.advance();
+                self.begin_scope();
+                self.block()?;
+                self.end_scope();
+                Ok(())
+            }
+            _ => self.expression_statement(),
+        }
+    }
+
+    fn for_statement(&mut self) -> CompileResult<()> {
+        self.begin_scope();
+        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
+
+        if self.match_any(&[TokenType::Semicolon]) {
+            // No initializer.
+        } else if self.match_any(&[TokenType::Var]) {
+            self.var_declaration()?;
+        } else {
+            self.expression_statement()?;
+        }
+
+        let mut loop_start = self.chunk().len();
+
+        let mut exit_jump = None;
+        if !self.match_any(&[TokenType::Semicolon]) {
+            self.expression()?;
+            self.consume(TokenType::Semicolon, "Expect ';' after loop condition.")?;
+
+            exit_jump = Some(self.emit_jump(OpCode::JumpIfFalse));
+            self.emit_op(OpCode::Pop);
+        }
+
+        // The increment comes first in the bytecode, so jump over it into
+        // the body, and loop back to it from the end of the body.
+        if !self.match_any(&[TokenType::RightParen]) {
+            let body_jump = self.emit_jump(OpCode::Jump);
+            let increment_start = self.chunk().len();
+
+            self.expression()?;
+            self.emit_op(OpCode::Pop);
+            self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
+
+            self.emit_loop(loop_start);
+            loop_start = increment_start;
+            self.patch_jump(body_jump);
+        }
+
+        self.statement()?;
+        self.emit_loop(loop_start);
+
+        if let Some(exit_jump) = exit_jump {
+            self.patch_jump(exit_jump);
+            self.emit_op(OpCode::Pop);
+        }
+
+        self.end_scope();
+
+        Ok(())
+    }
+
+    fn if_statement(&mut self) -> CompileResult<()> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
+        self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after if condition.")?;
+
+        let then_jump = self.emit_jump(OpCode::JumpIfFalse);
+        self.emit_op(OpCode::Pop);
+        self.statement()?;
+
+        let else_jump = self.emit_jump(OpCode::Jump);
+        self.patch_jump(then_jump);
+        self.emit_op(OpCode::Pop);
+
+        if self.match_any(&[TokenType::Else]) {
+            self.statement()?;
+        }
+        self.patch_jump(else_jump);
+
+        Ok(())
+    }
+
+    fn print_statement(&mut self) -> CompileResult<()> {
+        self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
+        self.emit_op(OpCode::Print);
+
+        Ok(())
+    }
+
+    fn return_statement(&mut self) -> CompileResult<()> {
+        let keyword = self.previous().clone();
+
+        if self.function_state().function_type == FunctionType::Script {
+            self.resolve_error(&Code::TOP_LEVEL_RETURN, &keyword, "Can't return from top-level code.");
+        }
+
+        if self.match_any(&[TokenType::Semicolon]) {
+            let semicolon = self.previous().clone();
+            self.emit_return(&semicolon);
+            return Ok(());
+        }
+
+        if self.function_state().function_type == FunctionType::Initializer {
+            self.resolve_error(&Code::INITIALIZER_RETURN_VALUE, &keyword, "Can't return a value from an initializer.");
+        }
+
+        self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after return value.")?;
+        self.emit_op(OpCode::Return);
+
+        Ok(())
+    }
+
+    fn while_statement(&mut self) -> CompileResult<()> {
+        let loop_start = self.chunk().len();
+
+        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
+        self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
+
+        let exit_jump = self.emit_jump(OpCode::JumpIfFalse);
+        self.emit_op(OpCode::Pop);
+        self.statement()?;
+        self.emit_loop(loop_start);
+
+        self.patch_jump(exit_jump);
+        self.emit_op(OpCode::Pop);
+
+        Ok(())
+    }
+
+    fn block(&mut self) -> CompileResult<()> {
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            self.declaration();
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+
+        Ok(())
+    }
+
+    fn expression_statement(&mut self) -> CompileResult<()> {
+        self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
+        self.emit_op(OpCode::Pop);
+
+        Ok(())
+    }
+
+    // Expressions, by precedence climbing.
+
+    fn expression(&mut self) -> CompileResult<()> {
+        self.parse_precedence(Precedence::Assignment)
+    }
+
+    fn parse_precedence(&mut self, precedence: Precedence) -> CompileResult<()> {
+        let Some(prefix) = Self::prefix_rule(self.peek().token_type()) else {
+            return Err(self.error(&Code::EXPECTED_EXPRESSION, self.peek(), "Expect expression."));
+        };
+        self.advance();
+
+        // Only a target parsed at the lowest precedence can be assigned
+        // to, so that `a + b = c` isn't compiled as `a + (b = c)`.
+        let can_assign = precedence <= Precedence::Assignment;
+        prefix(self, can_assign)?;
+
+        while precedence <= Self::infix_precedence(self.peek().token_type()) {
+            let infix = Self::infix_rule(self.peek().token_type())
+                .expect("tokens with a precedence have an infix rule");
+            self.advance();
+            infix(self, can_assign)?;
+        }
+
+        if can_assign && self.match_any(&[TokenType::Equal]) {
+            // Reported, but there is no need to synchronize: the compiler
+            // is not confused about where it is.
+            let parse_error = self.error(&Code::INVALID_ASSIGNMENT_TARGET, self.previous(), "Invalid assignment target.");
+            self.syntax_errors.push(parse_error);
+            self.expression()?;
+        }
+
+        Ok(())
+    }
+
+    fn prefix_rule(token_type: &TokenType) -> Option<ParseFn<'src, 'heap>> {
+        let rule: ParseFn<'src, 'heap> = match token_type {
+            TokenType::LeftParen => Self::grouping,
+            TokenType::Minus | TokenType::Bang => Self::unary,
+            TokenType::Identifier => Self::variable,
+            TokenType::String => Self::string,
+            TokenType::Number => Self::number,
+            TokenType::False | TokenType::True | TokenType::Nil => Self::literal,
+            TokenType::Super => Self::super_,
+            TokenType::This => Self::this,
+            _ => return None,
+        };
+
+        Some(rule)
+    }
+
+    fn infix_rule(token_type: &TokenType) -> Option<ParseFn<'src, 'heap>> {
+        let rule: ParseFn<'src, 'heap> = match token_type {
+            TokenType::LeftParen => Self::call,
+            TokenType::Dot => Self::dot,
+            TokenType::Minus
+            | TokenType::Plus
+            | TokenType::Slash
+            | TokenType::Star
+            | TokenType::BangEqual
+            | TokenType::EqualEqual
+            | TokenType::Greater
+            | TokenType::GreaterEqual
+            | TokenType::Less
+            | TokenType::LessEqual => Self::binary,
+            TokenType::And => Self::and,
+            TokenType::Or => Self::or,
+            _ => return None,
+        };
+
+        Some(rule)
+    }
+
+    fn infix_precedence(token_type: &TokenType) -> Precedence {
+        match token_type {
+            TokenType::LeftParen | TokenType::Dot => Precedence::Call,
+            TokenType::Slash | TokenType::Star => Precedence::Factor,
+            TokenType::Minus | TokenType::Plus => Precedence::Term,
+            TokenType::Greater
+            | TokenType::GreaterEqual
+            | TokenType::Less
+            | TokenType::LessEqual => Precedence::Comparison,
+            TokenType::BangEqual | TokenType::EqualEqual => Precedence::Equality,
+            TokenType::And => Precedence::And,
+            TokenType::Or => Precedence::Or,
+            _ => Precedence::None,
+        }
+    }
+
+    fn grouping(&mut self, _can_assign: bool) -> CompileResult<()> {
+        self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after expression.")?;
+
+        Ok(())
+    }
+
+    fn unary(&mut self, _can_assign: bool) -> CompileResult<()> {
+        let operator = self.previous().clone();
+        self.parse_precedence(Precedence::Unary)?;
+
+        // Runtime errors point at the operator, as in the tree-walker.
+        match operator.token_type() {
+            TokenType::Bang => self.emit_op_at(OpCode::Not, &operator),
+            TokenType::Minus => self.emit_op_at(OpCode::Negate, &operator),
+            _ => unreachable!("only `!` and `-` have the unary rule"),
+        }
+
+        Ok(())
+    }
+
+    fn binary(&mut self, _can_assign: bool) -> CompileResult<()> {
+        let operator = self.previous().clone();
+        self.parse_precedence(Self::infix_precedence(operator.token_type()).next())?;
+
+        match operator.token_type() {
+            TokenType::BangEqual => {
+                self.emit_op_at(OpCode::Equal, &operator);
+                self.emit_op_at(OpCode::Not, &operator);
+            }
+            TokenType::EqualEqual => self.emit_op_at(OpCode::Equal, &operator),
+            TokenType::Greater => self.emit_op_at(OpCode::Greater, &operator),
+            TokenType::GreaterEqual => {
+                self.emit_op_at(OpCode::Less, &operator);
+                self.emit_op_at(OpCode::Not, &operator);
+            }
+            TokenType::Less => self.emit_op_at(OpCode::Less, &operator),
+            TokenType::LessEqual => {
+                self.emit_op_at(OpCode::Greater, &operator);
+                self.emit_op_at(OpCode::Not, &operator);
+            }
+            TokenType::Plus => self.emit_op_at(OpCode::Add, &operator),
+            TokenType::Minus => self.emit_op_at(OpCode::Subtract, &operator),
+            TokenType::Star => self.emit_op_at(OpCode::Multiply, &operator),
+            TokenType::Slash => self.emit_op_at(OpCode::Divide, &operator),
+            _ => unreachable!("only arithmetic, comparison and equality have the binary rule"),
+        }
+
+        Ok(())
+    }
+
+    fn and(&mut self, _can_assign: bool) -> CompileResult<()> {
+        let end_jump = self.emit_jump(OpCode::JumpIfFalse);
+
+        self.emit_op(OpCode::Pop);
+        self.parse_precedence(Precedence::And)?;
+
+        self.patch_jump(end_jump);
+
+        Ok(())
+    }
+
+    fn or(&mut self, _can_assign: bool) -> CompileResult<()> {
+        let else_jump = self.emit_jump(OpCode::JumpIfFalse);
+        let end_jump = self.emit_jump(OpCode::Jump);
+
+        self.patch_jump(else_jump);
+        self.emit_op(OpCode::Pop);
+        self.parse_precedence(Precedence::Or)?;
+
+        self.patch_jump(end_jump);
+
+        Ok(())
+    }
+
+    fn call(&mut self, _can_assign: bool) -> CompileResult<()> {
+        let arg_count = self.argument_list()?;
+        self.emit_op_arg(OpCode::Call, arg_count);
+
+        Ok(())
+    }
+
+    fn argument_list(&mut self) -> CompileResult<u8> {
+        let mut arg_count = 0;
+
+        if !self.check(&TokenType::RightParen) {
+            loop {
+                if arg_count >= MAX_ARGUMENTS {
+                    let parse_error = self.error(&Code::TOO_MANY_ARGUMENTS, self.peek(), "Can't have more than 255 arguments.");
+                    self.syntax_errors.push(parse_error);
+                }
+
+                self.expression()?;
+                arg_count += 1;
+
+                if !self.match_any(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+
+        self.consume(TokenType::RightParen, "Expect ')' after arguments.")?;
+
+        // Past the limit the error is already reported, and the chunk will
+        // never run.
+        Ok(arg_count.min(MAX_ARGUMENTS) as u8)
+    }
+
+    fn dot(&mut self, can_assign: bool) -> CompileResult<()> {
+        let name = self.consume(TokenType::Identifier, "Expect property name after '.'.")?.clone();
+        let constant = self.identifier_constant(name.name());
+
+        if can_assign && self.match_any(&[TokenType::Equal]) {
+            self.expression()?;
+            self.emit_op_arg_at(OpCode::SetProperty, constant, &name);
+        } else if self.match_any(&[TokenType::LeftParen]) {
+            // `object.method(...)` in one instruction, without creating a
+            // bound method.
+            let arg_count = self.argument_list()?;
+            self.emit_op_arg(OpCode::Invoke, constant);
+            self.emit_byte(arg_count);
+        } else {
+            self.emit_op_arg_at(OpCode::GetProperty, constant, &name);
+        }
+
+        Ok(())
+    }
+
+    fn literal(&mut self, _can_assign: bool) -> CompileResult<()> {
+        match self.previous().token_type() {
+            TokenType::False => self.emit_op(OpCode::False),
+            TokenType::True => self.emit_op(OpCode::True),
+            TokenType::Nil => self.emit_op(OpCode::Nil),
+            _ => unreachable!("only `false`, `true` and `nil` have the literal rule"),
+        }
+
+        Ok(())
+    }
+
+    fn number(&mut self, _can_assign: bool) -> CompileResult<()> {
+        let Some(&LiteralType::Number(value)) = self.previous().literal() else {
+            unreachable!("number tokens carry their value");
+        };
+
+        self.emit_constant(Value::Number(value));
+
+        Ok(())
+    }
+
+    fn string(&mut self, _can_assign: bool) -> CompileResult<()> {
+        let Some(LiteralType::String(value)) = self.previous().literal() else {
+            unreachable!("string tokens carry their value");
+        };
+
+        let value = value.to_string();
+        let string = self.heap.intern(&value);
+        self.emit_constant(Value::Obj(string));
+
+        Ok(())
+    }
+
+    fn variable(&mut self, can_assign: bool) -> CompileResult<()> {
+        let name = self.previous().clone();
+
+        self.named_variable(name.name(), &name, can_assign)
+    }
+
+    fn this(&mut self, _can_assign: bool) -> CompileResult<()> {
+        let keyword = self.previous().clone();
+
+        if self.classes.is_empty() {
+            self.resolve_error(&Code::THIS_OUTSIDE_CLASS, &keyword, "Can't use 'this' outside of a class.");
+            return Ok(());
+        }
+
+        self.named_variable("this", &keyword, false)
+    }
+
+    fn super_(&mut self, _can_assign: bool) -> CompileResult<()> {
+        let keyword = self.previous().clone();
+
+        match self.classes.last() {
+            None => self.resolve_error(&Code::SUPER_OUTSIDE_CLASS, &keyword, "Can't use 'super' outside of a class."),
+            Some(class) if !class.has_superclass => {
+                self.resolve_error(&Code::SUPER_WITHOUT_SUPERCLASS, &keyword, "Can't use 'super' in a class with no superclass.");
+            }
+            Some(_) => {}
+        }
+
+        self.consume(TokenType::Dot, "Expect '.' after 'super'.")?;
+        let method = self.consume(TokenType::Identifier, "Expect superclass method name.")?.clone();
+        let constant = self.identifier_constant(method.name());
+
+        self.named_variable("this", &keyword, false)?;
+
+        if self.match_any(&[TokenType::LeftParen]) {
+            let arg_count = self.argument_list()?;
+            self.named_variable("super", &keyword, false)?;
+            self.emit_op_arg(OpCode::SuperInvoke, constant);
+            self.emit_byte(arg_count);
+        } else {
+            self.named_variable("super", &keyword, false)?;
+            self.emit_op_arg_at(OpCode::GetSuper, constant, &method);
+        }
+
+        Ok(())
+    }
+
+    /// Emits a read of `name`, or an assignment to it if `can_assign` and
+    /// an `=` follows. `token` is where errors about it are reported.
+    fn named_variable(&mut self, name: &str, token: &Token<'src>, can_assign: bool) -> CompileResult<()> {
+        let is_assignment = can_assign && self.check(&TokenType::Equal);
+        let innermost = self.functions.len() - 1;
+        self.lints.mark_used(name);
+
+        let (get_op, set_op, arg) = if let Some(slot) = self.resolve_local(innermost, name, token, !is_assignment) {
+            (OpCode::GetLocal, OpCode::SetLocal, slot)
+        } else if let Some(index) = self.resolve_upvalue(innermost, name, token) {
+            (OpCode::GetUpvalue, OpCode::SetUpvalue, index)
+        } else {
+            let constant = self.identifier_constant(name);
+            (OpCode::GetGlobal, OpCode::SetGlobal, constant)
+        };
+
+        if is_assignment {
+            self.advance();
+            self.expression()?;
+            self.emit_op_arg_at(set_op, arg, token);
+        } else {
+            self.emit_op_arg_at(get_op, arg, token);
+        }
+
+        Ok(())
+    }
+
+    // Variables and scopes.
+
+    /// Consumes a variable name and declares it. Returns the constant
+    /// holding the name for globals, and 0 for locals, which need none.
+    fn parse_variable(&mut self, message: &str) -> CompileResult<u8> {
+        let name = self.consume(TokenType::Identifier, message)?.clone();
+
+        self.declare_variable(&name);
+        if self.function_state().scope_depth > 0 {
+            return Ok(0);
+        }
+
+        Ok(self.identifier_constant(name.name()))
+    }
+
+    fn declare_variable(&mut self, name: &Token<'src>) {
+        let state = self.function_state();
+        if state.scope_depth == 0 {
+            return;
+        }
+
+        let first_declaration = state
+            .locals
+            .iter()
+            .rev()
+            .take_while(|local| local.depth.is_none_or(|depth| depth >= state.scope_depth))
+            .find(|local| local.name == name.name())
+            .map(|local| local.declaration);
+
+        if let Some(first_declaration) = first_declaration {
+            let resolve_error = ResolveError::new(
+                &Code::DUPLICATE_DECLARATION,
+                name.line(),
+                name.span(),
+                name.name().to_string(),
+                "Already a variable with this name in this scope.".to_string()
+            );
+            self.resolve_errors.push(resolve_error.with_first_declaration(first_declaration));
+        }
+
+        self.add_local(name.name(), name.span());
+        self.lints.declare(name.name(), name.line(), name.span());
+    }
+
+    fn add_local(&mut self, name: &str, declaration: Option<Span>) {
+        if self.function_state().locals.len() >= MAX_SLOTS {
+            let parse_error = self.error(&Code::TOO_MANY_LOCALS, self.previous(), "Too many local variables in function.");
+            self.syntax_errors.push(parse_error);
+            return;
+        }
+
+        self.function_state_mut().locals.push(Local {
+            name: name.to_string(),
+            depth: None,
+            is_captured: false,
+            declaration,
+        });
+    }
+
+    fn mark_initialized(&mut self) {
+        let state = self.function_state_mut();
+        if state.scope_depth == 0 {
+            return;
+        }
+
+        let scope_depth = state.scope_depth;
+        if let Some(local) = state.locals.last_mut() {
+            local.depth = Some(scope_depth);
+        }
+    }
+
+    fn define_variable(&mut self, global: u8) {
+        if self.function_state().scope_depth > 0 {
+            self.mark_initialized();
+            return;
+        }
+
+        self.emit_op_arg(OpCode::DefineGlobal, global);
+    }
+
+    /// The stack slot of the local `name` in the function at `depth` of the
+    /// nesting, if it has one.
+    fn resolve_local(&mut self, depth: usize, name: &str, token: &Token<'src>, is_read: bool) -> Option<u8> {
+        let (slot, is_initialized) = self.functions[depth]
+            .locals
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, local)| local.name == name)
+            .map(|(slot, local)| (slot, local.depth.is_some()))?;
+
+        if !is_initialized && is_read {
+            self.resolve_error(&Code::SELF_REFERENCING_INITIALIZER, token, "Can't read local variable in its own initializer.");
+        }
+
+        Some(slot as u8)
+    }
+
+    /// The upvalue through which the function at `depth` of the nesting
+    /// reaches the variable `name` of an enclosing function, if any.
+    fn resolve_upvalue(&mut self, depth: usize, name: &str, token: &Token<'src>) -> Option<u8> {
+        let enclosing = depth.checked_sub(1)?;
+
+        if let Some(slot) = self.resolve_local(enclosing, name, token, true) {
+            self.functions[enclosing].locals[slot as usize].is_captured = true;
+            return Some(self.add_upvalue(depth, slot, true));
+        }
+
+        let index = self.resolve_upvalue(enclosing, name, token)?;
+        Some(self.add_upvalue(depth, index, false))
+    }
+
+    fn add_upvalue(&mut self, depth: usize, index: u8, is_local: bool) -> u8 {
+        let upvalue = Upvalue { index, is_local };
+        let upvalues = &self.functions[depth].upvalues;
+
+        if let Some(existing) = upvalues.iter().position(|other| *other == upvalue) {
+            return existing as u8;
+        }
+
+        if upvalues.len() >= MAX_SLOTS {
+            let parse_error = self.error(&Code::TOO_MANY_UPVALUES, self.previous(), "Too many closure variables in function.");
+            self.syntax_errors.push(parse_error);
+            return 0;
+        }
+
+        self.functions[depth].upvalues.push(upvalue);
+        (self.functions[depth].upvalues.len() - 1) as u8
+    }
+
+    fn begin_scope(&mut self) {
+        self.function_state_mut().scope_depth += 1;
+        self.lints.begin_scope();
+    }
+
+    fn end_scope(&mut self) {
+        self.function_state_mut().scope_depth -= 1;
+        self.lints.end_scope();
+
+        loop {
+            let state = self.function_state();
+            let Some(local) = state.locals.last() else {
+                break;
+            };
+            if local.depth.is_none_or(|depth| depth <= state.scope_depth) {
+                break;
+            }
+
+            // Captured variables move off the stack, into their upvalue.
+            match local.is_captured {
+                true => self.emit_op(OpCode::CloseUpvalue),
+                false => self.emit_op(OpCode::Pop),
+            }
+            self.function_state_mut().locals.pop();
+        }
+    }
+
+    /// Finishes the innermost function, whose implicit `return` is placed
+    /// at `end`.
+    fn end_function(&mut self, end: &Token<'src>) -> (ObjRef, Vec<Upvalue>) {
+        self.emit_return(end);
+
+        let state = self.functions.pop().expect("a function is always being compiled");
+        let function = self.heap.alloc(Object::Function(ObjFunction {
+            arity: state.arity,
+            upvalue_count: state.upvalues.len(),
+            chunk: Rc::new(state.chunk),
+            name: state.name,
+        }));
+
+        (function, state.upvalues)
+    }
+
+    fn function_state(&self) -> &FunctionState {
+        self.functions.last().expect("a function is always being compiled")
+    }
+
+    fn function_state_mut(&mut self) -> &mut FunctionState {
+        self.functions.last_mut().expect("a function is always being compiled")
+    }
+
+    // Bytecode emission.
+
+    fn chunk(&self) -> &Chunk {
+        &self.function_state().chunk
+    }
+
+    /// Emits `byte` as compiled from the token just consumed.
+    fn emit_byte(&mut self, byte: u8) {
+        let (line, span) = (self.previous().line(), self.previous().span());
+        self.function_state_mut().chunk.write(byte, line, span);
+    }
+
+    fn emit_op(&mut self, op_code: OpCode) {
+        self.emit_byte(op_code.into());
+    }
+
+    /// Emits `op_code` as compiled from `token`, which runtime errors it
+    /// raises point at.
+    fn emit_op_at(&mut self, op_code: OpCode, token: &Token<'src>) {
+        self.function_state_mut().chunk.write_op(op_code, token.line(), token.span());
+    }
+
+    fn emit_op_arg(&mut self, op_code: OpCode, arg: u8) {
+        let token = self.previous().clone();
+        self.emit_op_arg_at(op_code, arg, &token);
+    }
+
+    fn emit_op_arg_at(&mut self, op_code: OpCode, arg: u8, token: &Token<'src>) {
+        let chunk = &mut self.function_state_mut().chunk;
+        chunk.write_op(op_code, token.line(), token.span());
+        chunk.write(arg, token.line(), token.span());
+    }
+
+    fn emit_return(&mut self, token: &Token<'src>) {
+        match self.function_state().function_type {
+            FunctionType::Initializer => self.emit_op_arg_at(OpCode::GetLocal, 0, token),
+            _ => self.emit_op_at(OpCode::Nil, token),
+        }
+
+        self.emit_op_at(OpCode::Return, token);
+    }
+
+    fn emit_constant(&mut self, value: Value) {
+        let constant = self.make_constant(value);
+        self.emit_op_arg(OpCode::Constant, constant);
+    }
+
+    /// The index of `value` in the constants table, adding it unless an
+    /// identical constant is already there.
+    fn make_constant(&mut self, value: Value) -> u8 {
+        let state = self.function_state_mut();
+        if let Some(constant) = state.chunk.find_constant(value) {
+            return constant as u8;
+        }
+
+        if state.chunk.constants().len() >= MAX_SLOTS {
+            // Once per function, rather than for every constant past the
+            // limit.
+            if !state.too_many_constants {
+                state.too_many_constants = true;
+
+                let parse_error = self.error(&Code::TOO_MANY_CONSTANTS, self.previous(), "Too many constants in one chunk.");
+                self.syntax_errors.push(parse_error);
+            }
+            return 0;
+        }
+
+        state.chunk.add_constant(value) as u8
+    }
+
+    fn identifier_constant(&mut self, name: &str) -> u8 {
+        let name = self.heap.intern(name);
+        self.make_constant(Value::Obj(name))
+    }
+
+    /// Emits a jump with a placeholder offset, returning where to patch it.
+    fn emit_jump(&mut self, op_code: OpCode) -> usize {
+        self.emit_op(op_code);
+        self.emit_byte(0xff);
+        self.emit_byte(0xff);
+
+        self.chunk().len() - 2
+    }
+
+    fn patch_jump(&mut self, offset: usize) {
+        // Relative to the end of the jump's operand.
+        let jump = self.chunk().len() - offset - 2;
+
+        if jump > u16::MAX as usize {
+            let parse_error = self.error(&Code::JUMP_TOO_LARGE, self.previous(), "Too much code to jump over.");
+            self.syntax_errors.push(parse_error);
+        }
+
+        let chunk = &mut self.function_state_mut().chunk;
+        chunk.patch(offset, (jump >> 8) as u8);
+        chunk.patch(offset + 1, jump as u8);
+    }
+
+    fn emit_loop(&mut self, loop_start: usize) {
+        self.emit_op(OpCode::Loop);
+
+        let offset = self.chunk().len() - loop_start + 2;
+        if offset > u16::MAX as usize {
+            let parse_error = self.error(&Code::JUMP_TOO_LARGE, self.previous(), "Loop body too large.");
+            self.syntax_errors.push(parse_error);
+        }
+
+        self.emit_byte((offset >> 8) as u8);
+        self.emit_byte(offset as u8);
+    }
+
+    // Error recovery.
+
+    fn checkpoint(&self) -> Checkpoint {
+        let state = self.function_state();
+
+        Checkpoint {
+            functions: self.functions.len(),
+            classes: self.classes.len(),
+            locals: state.locals.len(),
+            scope_depth: state.scope_depth,
+            lints: self.lints.depth(),
+        }
+    }
+
+    /// Forgets the functions, classes and scopes a failed declaration
+    /// started. Its bytecode is left as is: it will never run.
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.functions.truncate(checkpoint.functions);
+        self.classes.truncate(checkpoint.classes);
+
+        let state = self.function_state_mut();
+        state.locals.truncate(checkpoint.locals);
+        state.scope_depth = checkpoint.scope_depth;
+        self.lints.truncate(checkpoint.lints);
+    }
+
+    /// Discards tokens until the probable start of the next statement, so
+    /// one mistake does not cascade into many errors.
+    fn synchronize(&mut self) {
+        self.advance();
+
+        while !self.is_at_end() {
+            if self.previous().token_type() == &TokenType::Semicolon {
+                return;
+            }
+
+            match self.peek().token_type() {
+                TokenType::Class
+                | TokenType::Fun
+                | TokenType::Var
+                | TokenType::For
+                | TokenType::If
+                | TokenType::While
+                | TokenType::Print
+                | TokenType::Return => return,
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    // Token stream.
+
+    fn match_any(&mut self, token_types: &[TokenType]) -> bool {
+        if token_types.iter().any(|token_type| self.check(token_type)) {
+            self.advance();
+            return true;
+        }
+
+        false
+    }
+
+    fn consume(&mut self, token_type: TokenType, message: &str) -> CompileResult<&Token<'src>> {
+        if self.check(&token_type) {
+            return Ok(self.advance());
+        }
+
+        Err(self.error(&Code::EXPECTED_TOKEN, self.peek(), message))
+    }
+
+    fn check(&self, token_type: &TokenType) -> bool {
+        !self.is_at_end() && self.peek().token_type() == token_type
+    }
+
+    fn advance(&mut self) -> &Token<'src> {
+        if !self.is_at_end() {
+            self.current += 1;
+        }
+
+        self.previous()
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.peek().token_type() == &TokenType::Eof
+    }
+
+    fn peek(&self) -> &Token<'src> {
+        &self.tokens[self.current]
+    }
+
+    fn previous(&self) -> &Token<'src> {
+        &self.tokens[self.current.saturating_sub(1)]
+    }
+
+    fn error(&self, code: &'static Code, token: &Token<'src>, message: &str) -> ParseError {
+        let lexeme = match token.token_type() {
+            TokenType::Eof => None,
+            _ => Some(token.lexeme().to_string()),
+        };
+
+        ParseError::new(code, token.line(), token.span(), lexeme, message.to_string())
+    }
+
+    fn resolve_error(&mut self, code: &'static Code, token: &Token<'src>, message: &str) {
+        self.resolve_errors.push(
+            ResolveError::new(
+                code,
+                token.line(),
+                token.span(),
+                token.name().to_string(),
+                message.to_string()
+            )
+        );
+    }
+}
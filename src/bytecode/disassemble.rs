@@ -0,0 +1,322 @@
+use super::{Chunk, Heap, ObjRef, Object, OpCode, Value};
+
+use std::fmt::Write;
+
+/// Lists the bytecode of `function` and of every function nested in it,
+/// in the format of clox's `debug.c`. Nested functions come first, in the
+/// order clox prints them as it finishes compiling each one.
+pub fn disassemble(function: ObjRef, heap: &Heap) -> String {
+    let mut listing = String::new();
+    disassemble_function(function, heap, &mut listing);
+
+    listing
+}
+
+fn disassemble_function(function: ObjRef, heap: &Heap, listing: &mut String) {
+    let function = heap.function(function);
+
+    for constant in function.chunk.constants() {
+        if let Value::Obj(object) = constant {
+            if let Object::Function(_) = heap.get(*object) {
+                disassemble_function(*object, heap, listing);
+            }
+        }
+    }
+
+    let name = match function.name {
+        Some(name) => heap.string(name),
+        None => "<script>",
+    };
+    listing.push_str(&disassemble_chunk(&function.chunk, name, heap));
+}
+
+/// Lists every instruction of `chunk` under a `== name ==` header.
+pub fn disassemble_chunk(chunk: &Chunk, name: &str, heap: &Heap) -> String {
+    let mut listing = format!("== {} ==\n", name);
+
+    let mut offset = 0;
+    while offset < chunk.len() {
+        offset = disassemble_instruction(chunk, offset, heap, &mut listing);
+    }
+
+    listing
+}
+
+/// Appends the instruction at `offset` to `listing`, returning the offset
+/// of the next instruction.
+pub fn disassemble_instruction(chunk: &Chunk, offset: usize, heap: &Heap, listing: &mut String) -> usize {
+    // Writing to a `String` can't fail.
+    let _ = write!(listing, "{:04} ", offset);
+
+    if offset > 0 && chunk.line(offset) == chunk.line(offset - 1) {
+        listing.push_str("   | ");
+    } else {
+        let _ = write!(listing, "{:4} ", chunk.line(offset));
+    }
+
+    let byte = chunk.code()[offset];
+    let Some(op_code) = OpCode::from_byte(byte) else {
+        let _ = writeln!(listing, "Unknown opcode {}", byte);
+        return offset + 1;
+    };
+
+    let name = op_code_name(op_code);
+    match op_code {
+        OpCode::Constant
+        | OpCode::GetGlobal
+        | OpCode::DefineGlobal
+        | OpCode::SetGlobal
+        | OpCode::GetProperty
+        | OpCode::SetProperty
+        | OpCode::GetSuper
+        | OpCode::Class
+        | OpCode::Method => constant_instruction(name, chunk, offset, heap, listing),
+        OpCode::GetLocal
+        | OpCode::SetLocal
+        | OpCode::GetUpvalue
+        | OpCode::SetUpvalue
+        | OpCode::Call => byte_instruction(name, chunk, offset, listing),
+        OpCode::Jump | OpCode::JumpIfFalse => jump_instruction(name, 1, chunk, offset, listing),
+        OpCode::Loop => jump_instruction(name, -1, chunk, offset, listing),
+        OpCode::Invoke | OpCode::SuperInvoke => invoke_instruction(name, chunk, offset, heap, listing),
+        OpCode::Closure => closure_instruction(chunk, offset, heap, listing),
+        OpCode::Nil
+        | OpCode::True
+        | OpCode::False
+        | OpCode::Pop
+        | OpCode::Equal
+        | OpCode::Greater
+        | OpCode::Less
+        | OpCode::Add
+        | OpCode::Subtract
+        | OpCode::Multiply
+        | OpCode::Divide
+        | OpCode::Not
+        | OpCode::Negate
+        | OpCode::Print
+        | OpCode::CloseUpvalue
+        | OpCode::Return
+        | OpCode::Inherit => {
+            let _ = writeln!(listing, "{}", name);
+            offset + 1
+        }
+    }
+}
+
+fn op_code_name(op_code: OpCode) -> &'static str {
+    match op_code {
+        OpCode::Constant => "OP_CONSTANT",
+        OpCode::Nil => "OP_NIL",
+        OpCode::True => "OP_TRUE",
+        OpCode::False => "OP_FALSE",
+        OpCode::Pop => "OP_POP",
+        OpCode::GetLocal => "OP_GET_LOCAL",
+        OpCode::SetLocal => "OP_SET_LOCAL",
+        OpCode::GetGlobal => "OP_GET_GLOBAL",
+        OpCode::DefineGlobal => "OP_DEFINE_GLOBAL",
+        OpCode::SetGlobal => "OP_SET_GLOBAL",
+        OpCode::GetUpvalue => "OP_GET_UPVALUE",
+        OpCode::SetUpvalue => "OP_SET_UPVALUE",
+        OpCode::GetProperty => "OP_GET_PROPERTY",
+        OpCode::SetProperty => "OP_SET_PROPERTY",
+        OpCode::GetSuper => "OP_GET_SUPER",
+        OpCode::Equal => "OP_EQUAL",
+        OpCode::Greater => "OP_GREATER",
+        OpCode::Less => "OP_LESS",
+        OpCode::Add => "OP_ADD",
+        OpCode::Subtract => "OP_SUBTRACT",
+        OpCode::Multiply => "OP_MULTIPLY",
+        OpCode::Divide => "OP_DIVIDE",
+        OpCode::Not => "OP_NOT",
+        OpCode::Negate => "OP_NEGATE",
+        OpCode::Print => "OP_PRINT",
+        OpCode::Jump => "OP_JUMP",
+        OpCode::JumpIfFalse => "OP_JUMP_IF_FALSE",
+        OpCode::Loop => "OP_LOOP",
+        OpCode::Call => "OP_CALL",
+        OpCode::Invoke => "OP_INVOKE",
+        OpCode::SuperInvoke => "OP_SUPER_INVOKE",
+        OpCode::Closure => "OP_CLOSURE",
+        OpCode::CloseUpvalue => "OP_CLOSE_UPVALUE",
+        OpCode::Return => "OP_RETURN",
+        OpCode::Class => "OP_CLASS",
+        OpCode::Inherit => "OP_INHERIT",
+        OpCode::Method => "OP_METHOD",
+    }
+}
+
+fn constant_instruction(name: &str, chunk: &Chunk, offset: usize, heap: &Heap, listing: &mut String) -> usize {
+    let constant = chunk.code()[offset + 1];
+    let value = chunk.constants()[constant as usize];
+    let _ = writeln!(listing, "{:<16} {:4} '{}'", name, constant, format_value(value, heap));
+
+    offset + 2
+}
+
+fn byte_instruction(name: &str, chunk: &Chunk, offset: usize, listing: &mut String) -> usize {
+    let slot = chunk.code()[offset + 1];
+    let _ = writeln!(listing, "{:<16} {:4}", name, slot);
+
+    offset + 2
+}
+
+/// Shows the jump's target, `sign` telling whether it jumps forward.
+fn jump_instruction(name: &str, sign: isize, chunk: &Chunk, offset: usize, listing: &mut String) -> usize {
+    let jump = u16::from_be_bytes([chunk.code()[offset + 1], chunk.code()[offset + 2]]);
+    let target = offset as isize + 3 + sign * jump as isize;
+    let _ = writeln!(listing, "{:<16} {:4} -> {}", name, offset, target);
+
+    offset + 3
+}
+
+fn invoke_instruction(name: &str, chunk: &Chunk, offset: usize, heap: &Heap, listing: &mut String) -> usize {
+    let constant = chunk.code()[offset + 1];
+    let arg_count = chunk.code()[offset + 2];
+    let value = chunk.constants()[constant as usize];
+    let _ = writeln!(
+        listing,
+        "{:<16} ({} args) {:4} '{}'",
+        name,
+        arg_count,
+        constant,
+        format_value(value, heap)
+    );
+
+    offset + 3
+}
+
+/// The closure's function, then one line per captured variable.
+fn closure_instruction(chunk: &Chunk, offset: usize, heap: &Heap, listing: &mut String) -> usize {
+    let constant = chunk.code()[offset + 1];
+    let value = chunk.constants()[constant as usize];
+    let _ = writeln!(listing, "{:<16} {:4} {}", "OP_CLOSURE", constant, format_value(value, heap));
+
+    let Value::Obj(function) = value else {
+        unreachable!("closures are made from function constants");
+    };
+
+    let mut offset = offset + 2;
+    for _ in 0..heap.function(function).upvalue_count {
+        let is_local = chunk.code()[offset];
+        let index = chunk.code()[offset + 1];
+        let kind = match is_local {
+            1 => "local",
+            _ => "upvalue",
+        };
+        let _ = writeln!(listing, "{:04}      |                     {} {}", offset, kind, index);
+
+        offset += 2;
+    }
+
+    offset
+}
+
+/// Like `print`, except that numbers are formatted as C's `%g` does.
+fn format_value(value: Value, heap: &Heap) -> String {
+    match value {
+        Value::Number(number) => format_g(number),
+        _ => value.display(heap).to_string(),
+    }
+}
+
+/// Formats `number` like C's `printf("%g")`: six significant digits,
+/// without trailing zeros, switching to an exponent for very large or
+/// small magnitudes.
+fn format_g(number: f64) -> String {
+    if number.is_nan() {
+        return "nan".to_string();
+    }
+    if number.is_infinite() {
+        return if number > 0.0 { "inf" } else { "-inf" }.to_string();
+    }
+    if number == 0.0 {
+        return if number.is_sign_negative() { "-0" } else { "0" }.to_string();
+    }
+
+    // Rounding to six significant digits first gives the exponent `%g`
+    // bases its choice on.
+    let scientific = format!("{:.5e}", number);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+
+    if !(-4..6).contains(&exponent) {
+        let mantissa = trim_fraction(mantissa);
+        let sign = if exponent < 0 { '-' } else { '+' };
+        return format!("{}e{}{:02}", mantissa, sign, exponent.abs());
+    }
+
+    let decimals = (5 - exponent) as usize;
+    trim_fraction(&format!("{:.*}", decimals, number)).to_string()
+}
+
+fn trim_fraction(digits: &str) -> &str {
+    match digits.contains('.') {
+        true => digits.trim_end_matches('0').trim_end_matches('.'),
+        false => digits,
+    }
+}
+
+#[test]
+fn test_format_g() {
+    assert_eq!(format_g(1.0), "1");
+    assert_eq!(format_g(-2.5), "-2.5");
+    assert_eq!(format_g(0.1), "0.1");
+    assert_eq!(format_g(1.23456789), "1.23457");
+    assert_eq!(format_g(123456.0), "123456");
+    assert_eq!(format_g(1234567.0), "1.23457e+06");
+    assert_eq!(format_g(0.0001), "0.0001");
+    assert_eq!(format_g(0.00001234), "1.234e-05");
+    assert_eq!(format_g(999999.5), "1e+06");
+    assert_eq!(format_g(f64::INFINITY), "inf");
+}
+
+#[test]
+fn test_disassemble_listing() {
+    use crate::tokenize::tokenize;
+    use super::compile;
+
+    let source = "var a = 1;\nfun f(x) {\n  fun g() { return x; }\n  return g;\n}\nwhile (a < 3) a = a + 1;\nprint a.b(2);\n";
+    let (tokens, _) = tokenize(source);
+    let mut heap = Heap::new();
+    let function = compile(tokens, &mut heap).unwrap();
+
+    let expected = "\
+== g ==
+0000    3 OP_GET_UPVALUE      0
+0002    | OP_RETURN
+0003    | OP_NIL
+0004    | OP_RETURN
+== f ==
+0000    3 OP_CLOSURE          0 <fn g>
+0002      |                     local 1
+0004    4 OP_GET_LOCAL        2
+0006    | OP_RETURN
+0007    5 OP_NIL
+0008    | OP_RETURN
+== <script> ==
+0000    1 OP_CONSTANT         1 '1'
+0002    | OP_DEFINE_GLOBAL    0 'a'
+0004    5 OP_CLOSURE          3 <fn f>
+0006    | OP_DEFINE_GLOBAL    2 'f'
+0008    6 OP_GET_GLOBAL       0 'a'
+0010    | OP_CONSTANT         4 '3'
+0012    | OP_LESS
+0013    | OP_JUMP_IF_FALSE   13 -> 28
+0016    | OP_POP
+0017    | OP_GET_GLOBAL       0 'a'
+0019    | OP_CONSTANT         1 '1'
+0021    | OP_ADD
+0022    | OP_SET_GLOBAL       0 'a'
+0024    | OP_POP
+0025    | OP_LOOP            25 -> 8
+0028    | OP_POP
+0029    7 OP_GET_GLOBAL       0 'a'
+0031    | OP_CONSTANT         6 '2'
+0033    | OP_INVOKE        (1 args)    5 'b'
+0036    | OP_PRINT
+0037    8 OP_NIL
+0038    | OP_RETURN
+";
+
+    pretty_assertions::assert_eq!(disassemble(function, &heap), expected);
+}
@@ -0,0 +1,418 @@
+use super::{
+    ObjBoundMethod, ObjClass, ObjClosure, ObjFunction, ObjInstance, ObjNative, ObjUpvalue, Object, Value,
+};
+
+use std::collections::HashMap;
+use std::mem::{size_of, size_of_val};
+use std::rc::Rc;
+
+// Bytes to allocate before the first collection.
+const INITIAL_NEXT_GC: usize = 1024 * 1024;
+// How much the heap may grow, relative to what survived, before the next.
+const HEAP_GROW_FACTOR: usize = 2;
+
+/// Handle to an object on the `Heap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjRef(u32);
+
+/// Owns every object the compiler and the VM allocate. Objects are
+/// referred to by `ObjRef` handles, and strings are interned so that equal
+/// strings share a handle.
+///
+/// Unreachable objects are reclaimed by a mark-and-sweep collector. The
+/// heap doesn't know its roots: whoever owns them marks them with
+/// `mark_value` and `mark_object`, then calls `collect`. Allocating never
+/// collects by itself, `should_collect` tells when it's time to.
+#[derive(Debug)]
+pub struct Heap {
+    objects: Vec<Option<Object>>,
+    // Per slot of `objects`: its mark, and the size it was last measured at.
+    marks: Vec<bool>,
+    sizes: Vec<usize>,
+    // Slots of `objects` that are free for reuse.
+    free: Vec<u32>,
+    // Weak: strings nothing else refers to are collected all the same.
+    strings: HashMap<Rc<str>, ObjRef>,
+    // Marked objects whose references are yet to be marked.
+    gray: Vec<ObjRef>,
+    bytes_allocated: usize,
+    next_gc: usize,
+}
+
+impl Heap {
+    pub fn new() -> Self {
+        Self {
+            objects: Vec::new(),
+            marks: Vec::new(),
+            sizes: Vec::new(),
+            free: Vec::new(),
+            strings: HashMap::new(),
+            gray: Vec::new(),
+            bytes_allocated: 0,
+            next_gc: INITIAL_NEXT_GC,
+        }
+    }
+
+    pub fn alloc(&mut self, object: Object) -> ObjRef {
+        let size = object_size(&object);
+        self.bytes_allocated += size;
+
+        match self.free.pop() {
+            Some(index) => {
+                self.objects[index as usize] = Some(object);
+                self.sizes[index as usize] = size;
+                ObjRef(index)
+            }
+            None => {
+                self.objects.push(Some(object));
+                self.marks.push(false);
+                self.sizes.push(size);
+                ObjRef(self.objects.len() as u32 - 1)
+            }
+        }
+    }
+
+    /// Measures `object` again after it grew in place, as when a field or
+    /// method was added to it, and counts the difference in
+    /// `bytes_allocated`.
+    pub fn resize(&mut self, object: ObjRef) {
+        let size = object_size(self.get(object));
+        let index = object.0 as usize;
+
+        self.bytes_allocated = self.bytes_allocated - self.sizes[index] + size;
+        self.sizes[index] = size;
+    }
+
+    /// The handle of the string `value`, allocating it the first time.
+    pub fn intern(&mut self, value: &str) -> ObjRef {
+        if let Some(&object) = self.strings.get(value) {
+            return object;
+        }
+
+        let value: Rc<str> = value.into();
+        let object = self.alloc(Object::String(value.clone()));
+        self.strings.insert(value, object);
+
+        object
+    }
+
+    pub fn get(&self, object: ObjRef) -> &Object {
+        self.objects[object.0 as usize]
+            .as_ref()
+            .expect("handles never outlive their object")
+    }
+
+    pub fn get_mut(&mut self, object: ObjRef) -> &mut Object {
+        self.objects[object.0 as usize]
+            .as_mut()
+            .expect("handles never outlive their object")
+    }
+
+    // Typed accessors, for handles whose kind of object is already known.
+
+    pub fn string(&self, object: ObjRef) -> &str {
+        match self.get(object) {
+            Object::String(value) => value,
+            other => unreachable!("expected a string, found {:?}", other),
+        }
+    }
+
+    pub fn function(&self, object: ObjRef) -> &ObjFunction {
+        match self.get(object) {
+            Object::Function(function) => function,
+            other => unreachable!("expected a function, found {:?}", other),
+        }
+    }
+
+    pub fn native(&self, object: ObjRef) -> &ObjNative {
+        match self.get(object) {
+            Object::Native(native) => native,
+            other => unreachable!("expected a native, found {:?}", other),
+        }
+    }
+
+    pub fn closure(&self, object: ObjRef) -> &ObjClosure {
+        match self.get(object) {
+            Object::Closure(closure) => closure,
+            other => unreachable!("expected a closure, found {:?}", other),
+        }
+    }
+
+    pub fn upvalue(&self, object: ObjRef) -> &ObjUpvalue {
+        match self.get(object) {
+            Object::Upvalue(upvalue) => upvalue,
+            other => unreachable!("expected an upvalue, found {:?}", other),
+        }
+    }
+
+    pub fn upvalue_mut(&mut self, object: ObjRef) -> &mut ObjUpvalue {
+        match self.get_mut(object) {
+            Object::Upvalue(upvalue) => upvalue,
+            other => unreachable!("expected an upvalue, found {:?}", other),
+        }
+    }
+
+    pub fn class(&self, object: ObjRef) -> &ObjClass {
+        match self.get(object) {
+            Object::Class(class) => class,
+            other => unreachable!("expected a class, found {:?}", other),
+        }
+    }
+
+    pub fn class_mut(&mut self, object: ObjRef) -> &mut ObjClass {
+        match self.get_mut(object) {
+            Object::Class(class) => class,
+            other => unreachable!("expected a class, found {:?}", other),
+        }
+    }
+
+    pub fn instance(&self, object: ObjRef) -> &ObjInstance {
+        match self.get(object) {
+            Object::Instance(instance) => instance,
+            other => unreachable!("expected an instance, found {:?}", other),
+        }
+    }
+
+    pub fn instance_mut(&mut self, object: ObjRef) -> &mut ObjInstance {
+        match self.get_mut(object) {
+            Object::Instance(instance) => instance,
+            other => unreachable!("expected an instance, found {:?}", other),
+        }
+    }
+
+    pub fn bound_method(&self, object: ObjRef) -> &ObjBoundMethod {
+        match self.get(object) {
+            Object::BoundMethod(bound) => bound,
+            other => unreachable!("expected a bound method, found {:?}", other),
+        }
+    }
+
+    // Garbage collection.
+
+    /// Bytes taken by the objects alive, as estimated when each was
+    /// allocated or last resized.
+    pub fn bytes_allocated(&self) -> usize {
+        self.bytes_allocated
+    }
+
+    /// The value of `bytes_allocated` past which a collection is due.
+    pub fn next_gc(&self) -> usize {
+        self.next_gc
+    }
+
+    pub fn should_collect(&self) -> bool {
+        self.bytes_allocated > self.next_gc
+    }
+
+    /// The number of objects alive.
+    pub fn object_count(&self) -> usize {
+        self.objects.len() - self.free.len()
+    }
+
+    /// Marks `value` as a root of the next `collect`.
+    pub fn mark_value(&mut self, value: Value) {
+        if let Value::Obj(object) = value {
+            self.mark_object(object);
+        }
+    }
+
+    /// Marks `object` as a root of the next `collect`.
+    pub fn mark_object(&mut self, object: ObjRef) {
+        let mark = &mut self.marks[object.0 as usize];
+        if *mark {
+            return;
+        }
+
+        *mark = true;
+        self.gray.push(object);
+    }
+
+    /// Frees every object not reachable from the roots marked since the
+    /// last collection, returning the number of bytes freed.
+    pub fn collect(&mut self) -> usize {
+        self.trace_references();
+
+        let marks = &self.marks;
+        self.strings.retain(|_, object| marks[object.0 as usize]);
+
+        let freed = self.sweep();
+        self.next_gc = (self.bytes_allocated * HEAP_GROW_FACTOR).max(INITIAL_NEXT_GC);
+
+        freed
+    }
+
+    fn trace_references(&mut self) {
+        while let Some(object) = self.gray.pop() {
+            self.blacken(object);
+        }
+    }
+
+    /// Marks everything `object` refers to.
+    fn blacken(&mut self, object: ObjRef) {
+        let mut references = Vec::new();
+        let mut values = Vec::new();
+
+        match self.get(object) {
+            Object::String(_) | Object::Native(_) => {}
+            Object::Function(function) => {
+                references.extend(function.name);
+                values.extend_from_slice(function.chunk.constants());
+            }
+            Object::Closure(closure) => {
+                references.push(closure.function);
+                references.extend_from_slice(&closure.upvalues);
+            }
+            Object::Upvalue(upvalue) => {
+                // Open upvalues point into the stack, which is a root.
+                if let ObjUpvalue::Closed(value) = upvalue {
+                    values.push(*value);
+                }
+            }
+            Object::Class(class) => {
+                references.push(class.name);
+                references.extend(class.methods.iter().flat_map(|(&name, &method)| [name, method]));
+            }
+            Object::Instance(instance) => {
+                references.push(instance.class);
+                for (&name, &value) in &instance.fields {
+                    references.push(name);
+                    values.push(value);
+                }
+            }
+            Object::BoundMethod(bound) => {
+                references.push(bound.method);
+                values.push(bound.receiver);
+            }
+        }
+
+        for reference in references {
+            self.mark_object(reference);
+        }
+        for value in values {
+            self.mark_value(value);
+        }
+    }
+
+    /// Frees the unmarked objects and clears the marks of the others.
+    fn sweep(&mut self) -> usize {
+        let mut freed = 0;
+
+        for (index, slot) in self.objects.iter_mut().enumerate() {
+            if slot.is_none() {
+                continue;
+            }
+
+            if self.marks[index] {
+                self.marks[index] = false;
+            } else {
+                *slot = None;
+                freed += self.sizes[index];
+                self.free.push(index as u32);
+            }
+        }
+
+        self.bytes_allocated -= freed;
+        freed
+    }
+}
+
+impl Default for Heap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An estimate of the memory `object` takes, its own and what it owns.
+fn object_size(object: &Object) -> usize {
+    let owned = match object {
+        Object::String(value) => value.len(),
+        Object::Function(function) => {
+            function.chunk.len() + size_of_val(function.chunk.constants())
+        }
+        Object::Closure(closure) => closure.upvalues.len() * size_of::<ObjRef>(),
+        Object::Class(class) => class.methods.capacity() * size_of::<(ObjRef, ObjRef)>(),
+        Object::Instance(instance) => instance.fields.capacity() * size_of::<(ObjRef, Value)>(),
+        Object::Native(_) | Object::Upvalue(_) | Object::BoundMethod(_) => 0,
+    };
+
+    size_of::<Object>() + owned
+}
+
+#[test]
+fn test_heap_interns_strings() {
+    let mut heap = Heap::new();
+
+    let hello = heap.intern("hello");
+    let world = heap.intern("world");
+
+    assert_eq!(heap.intern("hello"), hello);
+    assert_ne!(hello, world);
+    assert_eq!(heap.string(world), "world");
+}
+
+#[test]
+fn test_heap_collects_unreachable_objects() {
+    let mut heap = Heap::new();
+
+    let kept = heap.intern("kept");
+    let dropped = heap.intern("dropped");
+    let name = heap.intern("Point");
+    let class = heap.alloc(Object::Class(ObjClass {
+        name,
+        methods: HashMap::new(),
+    }));
+    let field = heap.intern("field");
+    let instance = heap.alloc(Object::Instance(ObjInstance {
+        class,
+        fields: HashMap::from([(field, Value::Obj(kept))]),
+    }));
+    assert_eq!(heap.object_count(), 6);
+
+    heap.mark_object(instance);
+    let freed = heap.collect();
+
+    // The instance keeps its class, field name and field value alive.
+    assert_eq!(heap.object_count(), 5);
+    assert_eq!(freed, object_size(&Object::String("dropped".into())));
+    assert_eq!(heap.string(kept), "kept");
+
+    // Interning didn't keep "dropped" alive, and its slot is reused.
+    assert_eq!(heap.intern("other"), dropped);
+
+    heap.collect();
+    assert_eq!(heap.object_count(), 0);
+    assert_eq!(heap.bytes_allocated(), 0);
+}
+
+#[test]
+fn test_heap_counts_objects_that_grow() {
+    let mut heap = Heap::new();
+
+    let name = heap.intern("Point");
+    let class = heap.alloc(Object::Class(ObjClass {
+        name,
+        methods: HashMap::new(),
+    }));
+    let instance = heap.alloc(Object::Instance(ObjInstance {
+        class,
+        fields: HashMap::new(),
+    }));
+    let before = heap.bytes_allocated();
+
+    for field in 0..1000 {
+        let field = heap.intern(&format!("field{}", field));
+        heap.instance_mut(instance).fields.insert(field, Value::Nil);
+        heap.resize(instance);
+    }
+
+    let fields = heap.instance(instance).fields.capacity() * size_of::<(ObjRef, Value)>();
+    let strings = (0..1000)
+        .map(|field| object_size(&Object::String(format!("field{}", field).into())))
+        .sum::<usize>();
+    assert_eq!(heap.bytes_allocated(), before + fields + strings);
+
+    // Freeing the instance gives back all it grew to.
+    heap.mark_object(class);
+    heap.collect();
+    assert_eq!(heap.bytes_allocated(), object_size(heap.get(class)) + object_size(heap.get(name)));
+}
@@ -0,0 +1,266 @@
+mod op_code;
+pub use op_code::OpCode;
+
+mod chunk;
+pub use chunk::Chunk;
+
+mod value;
+pub use value::{DisplayValue, Value};
+
+mod object;
+pub use object::{
+    ObjBoundMethod, ObjClass, ObjClosure, ObjFunction, ObjInstance, ObjNative, ObjUpvalue,
+    Object,
+};
+
+mod heap;
+pub use heap::{Heap, ObjRef};
+
+mod compile_error;
+pub use compile_error::CompileError;
+
+mod compiler;
+pub use compiler::Compiler;
+
+mod vm;
+pub use vm::Vm;
+
+mod disassemble;
+pub use disassemble::{disassemble, disassemble_chunk, disassemble_instruction};
+
+use crate::resolve::ResolveWarning;
+use crate::tokenize::Token;
+
+/// Compiles a whole program into `heap`, returning the function for its
+/// top-level code or every compile error found.
+pub fn compile(tokens: Vec<Token<'_>>, heap: &mut Heap) -> Result<ObjRef, Vec<CompileError>> {
+    compile_with_warnings(tokens, heap).map(|(function, _)| function)
+}
+
+/// Compiles like `compile`, also returning the warnings found in a program
+/// without errors.
+pub fn compile_with_warnings(tokens: Vec<Token<'_>>, heap: &mut Heap) -> Result<(ObjRef, Vec<ResolveWarning>), Vec<CompileError>> {
+    Compiler::new(tokens, heap).compile()
+}
+
+#[cfg(test)]
+use crate::parse::parse;
+#[cfg(test)]
+use crate::resolve::resolve_with_warnings;
+#[cfg(test)]
+use crate::tokenize::tokenize;
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[cfg(test)]
+use crate::interpret::SharedOutput;
+
+/// Compiles and runs `file_contents`, returning what it printed followed
+/// by the compile or runtime errors, if any.
+#[cfg(test)]
+fn run_to_string(file_contents: &str) -> String {
+    let (tokens, _) = tokenize(file_contents);
+
+    let output = SharedOutput::new();
+    let mut vm = Vm::with_output(Box::new(output.clone()));
+
+    let result = match compile(tokens, vm.heap_mut()) {
+        Ok(function) => vm.interpret(function).map_err(|runtime_error| runtime_error.to_string()),
+        Err(compile_errors) => Err(
+            compile_errors
+                .iter()
+                .map(|compile_error| compile_error.to_string())
+                .collect::<Vec<String>>()
+                .join("\n")
+        ),
+    };
+
+    let mut printed = output.contents();
+    if let Err(error) = result {
+        printed.push_str(&error);
+    }
+
+    printed
+}
+
+#[test]
+fn test_vm_expressions() {
+    assert_eq!(run_to_string("print 1 + 2 * 3;"), "7\n");
+    assert_eq!(run_to_string("print (10.40 * 2) / 2;"), "10.4\n");
+    assert_eq!(run_to_string("print \"foo\" + \"bar\" == \"foobar\";"), "true\n");
+    assert_eq!(run_to_string("print !nil; print 1 <= 2; print 1 != 1;"), "true\ntrue\nfalse\n");
+    assert_eq!(run_to_string("print nil or \"yes\"; print false and 1;"), "yes\nfalse\n");
+}
+
+#[test]
+fn test_vm_variables_and_control_flow() {
+    assert_eq!(run_to_string("var a = 1; { var a = 2; print a; } a = a + 1; print a;"), "2\n2\n");
+    assert_eq!(
+        run_to_string("for (var i = 0; i < 3; i = i + 1) if (i != 1) print i; else print \"one\";"),
+        "0\none\n2\n"
+    );
+    assert_eq!(run_to_string("var i = 0; while (i < 2) { print i; i = i + 1; }"), "0\n1\n");
+}
+
+#[test]
+fn test_vm_closures() {
+    let source = "
+        fun makeCounter() {
+          var count = 0;
+          fun counter() { count = count + 1; return count; }
+          return counter;
+        }
+        var counter = makeCounter();
+        counter();
+        print counter();
+        print makeCounter;
+    ";
+    assert_eq!(run_to_string(source), "2\n<fn makeCounter>\n");
+
+    // Closures created in a loop share the loop variable.
+    let source = "
+        var first; var second;
+        for (var i = 0; i < 2; i = i + 1) {
+          fun show() { print i; }
+          if (first == nil) first = show; else second = show;
+        }
+        first(); second();
+    ";
+    assert_eq!(run_to_string(source), "2\n2\n");
+}
+
+#[test]
+fn test_vm_classes() {
+    let source = "
+        class A {
+          init(name) { this.name = name; }
+          greet() { return \"hi \" + this.name; }
+        }
+        class B < A {
+          greet() { return super.greet() + \"!\"; }
+        }
+        var b = B(\"bob\");
+        print b.greet();
+        var greet = b.greet;
+        print greet();
+        print B; print b; print b.init(\"ann\").name;
+    ";
+    assert_eq!(run_to_string(source), "hi bob!\nhi bob!\nB\nB instance\nann\n");
+}
+
+#[test]
+fn test_vm_runtime_errors() {
+    assert_eq!(run_to_string("print 1;\n-\"a\";"), "1\nOperand must be a number.\n[line 2]");
+    assert_eq!(run_to_string("print x;"), "Undefined variable 'x'.\n[line 1]");
+    assert_eq!(run_to_string("fun f(a) {}\nf();"), "Expected 1 arguments but got 0.\n[line 2]");
+    assert_eq!(run_to_string("\"a\"();"), "Can only call functions and classes.\n[line 1]");
+    assert_eq!(run_to_string("class A {} A().x;"), "Undefined property 'x'.\n[line 1]");
+}
+
+#[test]
+fn test_vm_compile_errors() {
+    assert_eq!(run_to_string("print ;"), "[line 1] Error at ';': Expect expression.");
+    assert_eq!(run_to_string("1 + 2 = 3;"), "[line 1] Error at '=': Invalid assignment target.");
+    assert_eq!(
+        run_to_string("var a = 1\nprint a;\nfun (x) {}"),
+        "[line 2] Error at 'print': Expect ';' after variable declaration.\n\
+         [line 3] Error at '(': Expect function name."
+    );
+    assert_eq!(run_to_string("print 1;\nreturn;"), "[line 2] Error at 'return': Can't return from top-level code.");
+    // Static errors are only reported for programs without syntax errors.
+    assert_eq!(run_to_string("return;\nprint ;"), "[line 2] Error at ';': Expect expression.");
+}
+
+#[test]
+fn test_vm_error_codes() {
+    let compile_code = |source| {
+        let (tokens, _) = tokenize(source);
+        compile(tokens, &mut Heap::new()).unwrap_err()[0].code().id()
+    };
+
+    assert_eq!(compile_code("print ;"), "L0101");
+    assert_eq!(compile_code("print 1"), "L0102");
+    assert_eq!(compile_code("return;"), "L0203");
+    assert_eq!(compile_code("{ var a = 1; var a = 2; }"), "L0202");
+
+    let runtime_code = |source| {
+        let (tokens, _) = tokenize(source);
+        let mut vm = Vm::with_output(Box::new(SharedOutput::new()));
+        let function = compile(tokens, vm.heap_mut()).unwrap();
+
+        vm.interpret(function).unwrap_err().code().id()
+    };
+
+    assert_eq!(runtime_code("-\"a\";"), "L0301");
+    assert_eq!(runtime_code("print x;"), "L0304");
+    assert_eq!(runtime_code("class A {} A().x;"), "L0305");
+    assert_eq!(runtime_code("fun f(a) {} f();"), "L0307");
+    assert_eq!(runtime_code("1.size = 2;"), "L0309");
+}
+
+#[test]
+fn test_vm_warnings_match_the_resolver() {
+    let source = "\
+fun f(parameter) {
+  var unused;
+  var _kept;
+  var read = 1;
+  fun g() { var closed = read; fun h() { return closed; } var later; return h; }
+  for (var i = 0; i < 1; i = i + 1) {}
+}
+{ var outer = 1; { var outer = 2; print outer; } }
+class A < f { m() { var self = this; return super.m; } }";
+
+    let (tokens, _) = tokenize(source);
+    let (_, resolved) = resolve_with_warnings(&parse(tokens.clone()).unwrap()).unwrap();
+    let (_, compiled) = compile_with_warnings(tokens, &mut Heap::new()).unwrap();
+
+    assert_eq!(
+        compiled.iter().map(|warning| (warning.line(), warning.message())).collect::<Vec<(u32, &str)>>(),
+        vec![
+            (2, "Local variable 'unused' is never used."),
+            (5, "Local variable 'later' is never used."),
+            (8, "Local variable 'outer' is never used."),
+            (8, "Local variable 'outer' shadows a variable of an enclosing scope."),
+            (9, "Local variable 'self' is never used."),
+        ]
+    );
+    assert_eq!(compiled, resolved);
+}
+
+#[test]
+fn test_vm_constant_limit() {
+    // Repeated names and literals share one constant each.
+    let source = format!("var x = 0;\n{}print x;", "x = x + 1;\n".repeat(30_000));
+    assert_eq!(run_to_string(&source), "30000\n");
+
+    // Past the limit, the error is reported once per function.
+    let source = (0..300).map(|n| format!("print {};\n", n)).collect::<String>();
+    assert_eq!(run_to_string(&source), "[line 257] Error at '256': Too many constants in one chunk.");
+}
+
+#[test]
+fn test_vm_collects_garbage() {
+    let source = "
+        var kept = \"kept\";
+        for (var i = 0; i < 1000; i = i + 1) {
+          var garbage = \"garbage\" + \"!\";
+          fun capture() { return i; }
+        }
+    ";
+    let (tokens, _) = tokenize(source);
+
+    let mut vm = Vm::with_output(Box::new(std::io::sink())).with_gc_stress();
+    let function = compile(tokens, vm.heap_mut()).unwrap();
+    vm.interpret(function).unwrap();
+
+    // Each iteration allocated a closure and an upvalue, none of which
+    // outlived it.
+    assert!(vm.heap_mut().object_count() < 20);
+
+    let kept = vm.heap_mut().intern("kept");
+    let function = compile(tokenize("print kept;").0, vm.heap_mut()).unwrap();
+    vm.interpret(function).unwrap();
+    assert_eq!(vm.heap_mut().intern("kept"), kept);
+}
@@ -0,0 +1,70 @@
+use crate::interpret::NativeFunction;
+use super::{Chunk, ObjRef, Value};
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Everything the VM allocates on its heap.
+#[derive(Debug)]
+pub enum Object {
+    String(Rc<str>),
+    Function(ObjFunction),
+    Native(ObjNative),
+    Closure(ObjClosure),
+    Upvalue(ObjUpvalue),
+    Class(ObjClass),
+    Instance(ObjInstance),
+    BoundMethod(ObjBoundMethod),
+}
+
+/// A compiled function. Closures over it are created at runtime.
+#[derive(Debug)]
+pub struct ObjFunction {
+    pub arity: usize,
+    pub upvalue_count: usize,
+    // Shared with the call frames running it.
+    pub chunk: Rc<Chunk>,
+    // `None` for the top-level script.
+    pub name: Option<ObjRef>,
+}
+
+/// A native function, from the same registry as the tree-walk
+/// interpreter's.
+#[derive(Debug)]
+pub struct ObjNative {
+    pub function: Rc<NativeFunction>,
+}
+
+#[derive(Debug)]
+pub struct ObjClosure {
+    pub function: ObjRef,
+    pub upvalues: Vec<ObjRef>,
+}
+
+/// A variable captured by a closure. It points into the stack while the
+/// variable's scope is live, and holds the value itself after that.
+#[derive(Debug)]
+pub enum ObjUpvalue {
+    Open(usize),
+    Closed(Value),
+}
+
+#[derive(Debug)]
+pub struct ObjClass {
+    pub name: ObjRef,
+    // Keyed by interned method name, the values are closures.
+    pub methods: HashMap<ObjRef, ObjRef>,
+}
+
+#[derive(Debug)]
+pub struct ObjInstance {
+    pub class: ObjRef,
+    // Keyed by interned field name.
+    pub fields: HashMap<ObjRef, Value>,
+}
+
+#[derive(Debug)]
+pub struct ObjBoundMethod {
+    pub receiver: Value,
+    pub method: ObjRef,
+}
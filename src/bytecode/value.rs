@@ -0,0 +1,65 @@
+use super::{Heap, ObjRef, Object};
+
+use std::fmt;
+
+/// A value on the VM's stack. Anything bigger than a number lives on the
+/// heap and is referred to by handle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    // Strings are interned, so comparing handles compares their contents.
+    Obj(ObjRef),
+}
+
+impl Value {
+    /// `nil` and `false` are falsey, everything else is truthy.
+    pub fn is_falsey(&self) -> bool {
+        matches!(self, Value::Nil | Value::Bool(false))
+    }
+
+    /// Formats the value the way `print` shows it, which needs the heap
+    /// for anything stored there.
+    pub fn display(self, heap: &Heap) -> DisplayValue<'_> {
+        DisplayValue {
+            value: self,
+            heap,
+        }
+    }
+}
+
+pub struct DisplayValue<'heap> {
+    value: Value,
+    heap: &'heap Heap,
+}
+
+impl fmt::Display for DisplayValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let object = match self.value {
+            Value::Nil => return write!(f, "nil"),
+            Value::Bool(value) => return write!(f, "{}", value),
+            // Integral numbers print without a fractional part, like the
+            // tree-walk interpreter.
+            Value::Number(value) => return write!(f, "{}", value),
+            Value::Obj(object) => object,
+        };
+
+        let heap = self.heap;
+        match heap.get(object) {
+            Object::String(value) => write!(f, "{}", value),
+            Object::Function(function) => match function.name {
+                Some(name) => write!(f, "<fn {}>", heap.string(name)),
+                None => write!(f, "<script>"),
+            },
+            Object::Native(_) => write!(f, "<native fn>"),
+            Object::Closure(closure) => write!(f, "{}", Value::Obj(closure.function).display(heap)),
+            Object::Upvalue(_) => write!(f, "upvalue"),
+            Object::Class(class) => write!(f, "{}", heap.string(class.name)),
+            Object::Instance(instance) => {
+                write!(f, "{} instance", heap.string(heap.class(instance.class).name))
+            }
+            Object::BoundMethod(bound) => write!(f, "{}", Value::Obj(bound.method).display(heap)),
+        }
+    }
+}
@@ -0,0 +1,751 @@
+use crate::diagnostics::Code;
+use crate::interpret::{self, standard_library, NativeFunction, RuntimeError, MAX_CALL_DEPTH};
+use super::{
+    Chunk, Heap, ObjBoundMethod, ObjClass, ObjClosure, ObjInstance, ObjNative, ObjRef, ObjUpvalue,
+    Object, OpCode, Value,
+};
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+type RunResult<T> = Result<T, RuntimeError>;
+
+// The script's own frame is not a call, so allow one more than the
+// tree-walker's call depth to overflow at the same point.
+const FRAMES_MAX: usize = MAX_CALL_DEPTH + 1;
+
+// Natives only see values both backends have. Functions, classes and
+// instances of the VM don't exist for the tree-walk interpreter.
+const NATIVE_VALUES: &str = "Native functions only take and return nil, booleans, numbers, strings and natives.";
+
+/// A function invocation in progress.
+#[derive(Debug)]
+struct CallFrame {
+    closure: ObjRef,
+    // The closure's chunk, held directly to avoid a heap lookup per byte.
+    chunk: Rc<Chunk>,
+    ip: usize,
+    // Stack index of the frame's slot zero.
+    slots: usize,
+}
+
+/// Stack-based virtual machine running the bytecode of `Compiler`.
+///
+/// Like `Interpreter`, global state survives between calls to `interpret`.
+/// Programs must be compiled into the VM's own heap, see `heap_mut`.
+pub struct Vm {
+    heap: Heap,
+    stack: Vec<Value>,
+    // The running frame lives in `frame`; `frames` holds its callers.
+    frame: Option<CallFrame>,
+    frames: Vec<CallFrame>,
+    globals: HashMap<ObjRef, Value>,
+    // Upvalues still pointing into the stack, by ascending stack slot.
+    open_upvalues: Vec<ObjRef>,
+    init_string: ObjRef,
+    output: Box<dyn Write>,
+    // Collect on every allocation, to shake out missing roots.
+    gc_stress: bool,
+    // Report each collection on stderr.
+    gc_log: bool,
+}
+
+impl Vm {
+    pub fn new() -> Self {
+        Self::with_output(Box::new(io::stdout()))
+    }
+
+    /// A VM whose `print` statements write to `output`.
+    pub fn with_output(output: Box<dyn Write>) -> Self {
+        let mut heap = Heap::new();
+        let init_string = heap.intern("init");
+
+        let mut vm = Self {
+            heap,
+            stack: Vec::new(),
+            frame: None,
+            frames: Vec::new(),
+            globals: HashMap::new(),
+            open_upvalues: Vec::new(),
+            init_string,
+            output,
+            gc_stress: false,
+            gc_log: false,
+        };
+
+        for native in standard_library() {
+            vm.define_native(native);
+        }
+
+        vm
+    }
+
+    /// Collects garbage before every allocation rather than when the heap
+    /// has grown enough.
+    pub fn with_gc_stress(mut self) -> Self {
+        self.gc_stress = true;
+        self
+    }
+
+    /// Reports on stderr how many bytes each collection freed.
+    pub fn with_gc_log(mut self) -> Self {
+        self.gc_log = true;
+        self
+    }
+
+    /// The heap programs for this VM are compiled into.
+    pub fn heap_mut(&mut self) -> &mut Heap {
+        &mut self.heap
+    }
+
+    /// Runs `function`, the top-level code of a compiled program.
+    pub fn interpret(&mut self, function: ObjRef) -> Result<(), RuntimeError> {
+        // Nothing refers to the function yet, keep it on the stack while
+        // its closure is allocated.
+        self.push(Value::Obj(function));
+        let closure = self.alloc(Object::Closure(ObjClosure {
+            function,
+            upvalues: Vec::new(),
+        }));
+        self.pop();
+        self.push(Value::Obj(closure));
+
+        let result = self.call(closure, 0).and_then(|()| self.run());
+
+        if result.is_err() {
+            self.stack.clear();
+            self.frame = None;
+            self.frames.clear();
+            self.open_upvalues.clear();
+        }
+
+        result
+    }
+
+    /// Makes `native` callable from Lox as a global under its name,
+    /// replacing any global already bound to it.
+    pub fn define_native(&mut self, native: NativeFunction) {
+        let function = Rc::new(native);
+        let native = self.alloc(Object::Native(ObjNative { function: function.clone() }));
+        self.push(Value::Obj(native));
+        let name = self.intern(function.name());
+        self.pop();
+
+        self.globals.insert(name, Value::Obj(native));
+    }
+
+    fn run(&mut self) -> RunResult<()> {
+        loop {
+            let byte = self.read_byte();
+            let Some(op_code) = OpCode::from_byte(byte) else {
+                unreachable!("the compiler only emits valid opcodes, got {}", byte);
+            };
+
+            match op_code {
+                OpCode::Constant => {
+                    let constant = self.read_constant();
+                    self.push(constant);
+                }
+                OpCode::Nil => self.push(Value::Nil),
+                OpCode::True => self.push(Value::Bool(true)),
+                OpCode::False => self.push(Value::Bool(false)),
+                OpCode::Pop => {
+                    self.pop();
+                }
+                OpCode::GetLocal => {
+                    let slot = self.read_byte() as usize;
+                    let value = self.stack[self.frame().slots + slot];
+                    self.push(value);
+                }
+                OpCode::SetLocal => {
+                    let slot = self.read_byte() as usize;
+                    let index = self.frame().slots + slot;
+                    self.stack[index] = self.peek(0);
+                }
+                OpCode::GetGlobal => {
+                    let name = self.read_string();
+                    match self.globals.get(&name) {
+                        Some(&value) => self.push(value),
+                        None => return Err(self.undefined_variable(name)),
+                    }
+                }
+                OpCode::DefineGlobal => {
+                    let name = self.read_string();
+                    let value = self.pop();
+                    self.globals.insert(name, value);
+                }
+                OpCode::SetGlobal => {
+                    let name = self.read_string();
+                    let value = self.peek(0);
+                    match self.globals.get_mut(&name) {
+                        Some(slot) => *slot = value,
+                        None => return Err(self.undefined_variable(name)),
+                    }
+                }
+                OpCode::GetUpvalue => {
+                    let index = self.read_byte() as usize;
+                    let upvalue = self.heap.closure(self.frame().closure).upvalues[index];
+                    let value = match self.heap.upvalue(upvalue) {
+                        ObjUpvalue::Open(slot) => self.stack[*slot],
+                        ObjUpvalue::Closed(value) => *value,
+                    };
+                    self.push(value);
+                }
+                OpCode::SetUpvalue => {
+                    let index = self.read_byte() as usize;
+                    let upvalue = self.heap.closure(self.frame().closure).upvalues[index];
+                    let value = self.peek(0);
+                    match self.heap.upvalue_mut(upvalue) {
+                        ObjUpvalue::Open(slot) => self.stack[*slot] = value,
+                        ObjUpvalue::Closed(closed) => *closed = value,
+                    }
+                }
+                OpCode::GetProperty => {
+                    let name = self.read_string();
+                    let Some(instance) = self.as_instance(self.peek(0)) else {
+                        return Err(self.error(&Code::NOT_AN_INSTANCE, "Only instances have properties."));
+                    };
+
+                    if let Some(&value) = self.heap.instance(instance).fields.get(&name) {
+                        self.pop();
+                        self.push(value);
+                    } else {
+                        let class = self.heap.instance(instance).class;
+                        self.bind_method(class, name)?;
+                    }
+                }
+                OpCode::SetProperty => {
+                    let name = self.read_string();
+                    let Some(instance) = self.as_instance(self.peek(1)) else {
+                        return Err(self.error(&Code::NOT_AN_INSTANCE, "Only instances have fields."));
+                    };
+
+                    let value = self.pop();
+                    self.heap.instance_mut(instance).fields.insert(name, value);
+                    self.heap.resize(instance);
+                    self.pop();
+                    self.push(value);
+                }
+                OpCode::GetSuper => {
+                    let name = self.read_string();
+                    let Value::Obj(superclass) = self.pop() else {
+                        unreachable!("`super` is always a class");
+                    };
+                    self.bind_method(superclass, name)?;
+                }
+                OpCode::Equal => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    self.push(Value::Bool(left == right));
+                }
+                OpCode::Greater => self.binary_op(|left, right| Value::Bool(left > right))?,
+                OpCode::Less => self.binary_op(|left, right| Value::Bool(left < right))?,
+                OpCode::Add => match (self.peek(1), self.peek(0)) {
+                    (Value::Number(left), Value::Number(right)) => {
+                        self.pop();
+                        self.pop();
+                        self.push(Value::Number(left + right));
+                    }
+                    (Value::Obj(left), Value::Obj(right)) if self.is_string(left) && self.is_string(right) => {
+                        let value = format!("{}{}", self.heap.string(left), self.heap.string(right));
+                        let string = self.intern(&value);
+                        self.pop();
+                        self.pop();
+                        self.push(Value::Obj(string));
+                    }
+                    _ => return Err(self.error(&Code::INVALID_ADDITION, "Operands must be two numbers or two strings.")),
+                },
+                OpCode::Subtract => self.binary_op(|left, right| Value::Number(left - right))?,
+                OpCode::Multiply => self.binary_op(|left, right| Value::Number(left * right))?,
+                OpCode::Divide => self.binary_op(|left, right| Value::Number(left / right))?,
+                OpCode::Not => {
+                    let value = self.pop();
+                    self.push(Value::Bool(value.is_falsey()));
+                }
+                OpCode::Negate => {
+                    let Value::Number(value) = self.peek(0) else {
+                        return Err(self.error(&Code::OPERAND_NOT_NUMBER, "Operand must be a number."));
+                    };
+                    self.pop();
+                    self.push(Value::Number(-value));
+                }
+                OpCode::Print => {
+                    let value = self.pop();
+                    // A closed stdout is not the program's fault, keep going.
+                    let _ = writeln!(self.output, "{}", value.display(&self.heap));
+                }
+                OpCode::Jump => {
+                    let offset = self.read_short() as usize;
+                    self.frame_mut().ip += offset;
+                }
+                OpCode::JumpIfFalse => {
+                    let offset = self.read_short() as usize;
+                    if self.peek(0).is_falsey() {
+                        self.frame_mut().ip += offset;
+                    }
+                }
+                OpCode::Loop => {
+                    let offset = self.read_short() as usize;
+                    self.frame_mut().ip -= offset;
+                }
+                OpCode::Call => {
+                    let arg_count = self.read_byte() as usize;
+                    self.call_value(self.peek(arg_count), arg_count)?;
+                }
+                OpCode::Invoke => {
+                    let name = self.read_string();
+                    let arg_count = self.read_byte() as usize;
+                    self.invoke(name, arg_count)?;
+                }
+                OpCode::SuperInvoke => {
+                    let name = self.read_string();
+                    let arg_count = self.read_byte() as usize;
+                    let Value::Obj(superclass) = self.pop() else {
+                        unreachable!("`super` is always a class");
+                    };
+                    self.invoke_from_class(superclass, name, arg_count)?;
+                }
+                OpCode::Closure => {
+                    let Value::Obj(function) = self.read_constant() else {
+                        unreachable!("closures are made from function constants");
+                    };
+
+                    let upvalue_count = self.heap.function(function).upvalue_count;
+                    let mut upvalues = Vec::with_capacity(upvalue_count);
+                    for _ in 0..upvalue_count {
+                        let is_local = self.read_byte() == 1;
+                        let index = self.read_byte() as usize;
+
+                        let upvalue = match is_local {
+                            true => self.capture_upvalue(self.frame().slots + index),
+                            false => self.heap.closure(self.frame().closure).upvalues[index],
+                        };
+                        upvalues.push(upvalue);
+                    }
+
+                    let closure = self.alloc(Object::Closure(ObjClosure { function, upvalues }));
+                    self.push(Value::Obj(closure));
+                }
+                OpCode::CloseUpvalue => {
+                    self.close_upvalues(self.stack.len() - 1);
+                    self.pop();
+                }
+                OpCode::Return => {
+                    let result = self.pop();
+                    let slots = self.frame().slots;
+                    self.close_upvalues(slots);
+
+                    self.stack.truncate(slots);
+                    self.frame = self.frames.pop();
+
+                    // Returning from the script itself ends the program.
+                    if self.frame.is_none() {
+                        return Ok(());
+                    }
+
+                    self.push(result);
+                }
+                OpCode::Class => {
+                    let name = self.read_string();
+                    let class = self.alloc(Object::Class(ObjClass {
+                        name,
+                        methods: HashMap::new(),
+                    }));
+                    self.push(Value::Obj(class));
+                }
+                OpCode::Inherit => {
+                    let Some(superclass) = self.as_class(self.peek(1)) else {
+                        return Err(self.error(&Code::SUPERCLASS_NOT_CLASS, "Superclass must be a class."));
+                    };
+                    let Value::Obj(subclass) = self.peek(0) else {
+                        unreachable!("only classes inherit");
+                    };
+
+                    // Copied down now, so that method lookups never need to
+                    // walk the inheritance chain.
+                    let methods = self.heap.class(superclass).methods.clone();
+                    self.heap.class_mut(subclass).methods.extend(methods);
+                    self.heap.resize(subclass);
+                    self.pop();
+                }
+                OpCode::Method => {
+                    let name = self.read_string();
+                    let method = self.peek(0);
+                    let Value::Obj(class) = self.peek(1) else {
+                        unreachable!("methods are added to classes");
+                    };
+                    let Value::Obj(method) = method else {
+                        unreachable!("methods are closures");
+                    };
+
+                    self.heap.class_mut(class).methods.insert(name, method);
+                    self.heap.resize(class);
+                    self.pop();
+                }
+            }
+        }
+    }
+
+    // Calls.
+
+    fn call_value(&mut self, callee: Value, arg_count: usize) -> RunResult<()> {
+        let Value::Obj(object) = callee else {
+            return Err(self.error(&Code::NOT_CALLABLE, "Can only call functions and classes."));
+        };
+
+        match self.heap.get(object) {
+            Object::Closure(_) => self.call(object, arg_count),
+            Object::BoundMethod(ObjBoundMethod { receiver, method }) => {
+                let (receiver, method) = (*receiver, *method);
+                let receiver_slot = self.stack.len() - arg_count - 1;
+                self.stack[receiver_slot] = receiver;
+
+                self.call(method, arg_count)
+            }
+            Object::Class(class) => {
+                let initializer = class.methods.get(&self.init_string).copied();
+
+                let instance = self.alloc(Object::Instance(ObjInstance {
+                    class: object,
+                    fields: HashMap::new(),
+                }));
+                let receiver_slot = self.stack.len() - arg_count - 1;
+                self.stack[receiver_slot] = Value::Obj(instance);
+
+                match initializer {
+                    Some(initializer) => self.call(initializer, arg_count),
+                    None if arg_count != 0 => Err(self.arity_error(0, arg_count)),
+                    None => Ok(()),
+                }
+            }
+            Object::Native(native) => {
+                let native = native.function.clone();
+                if arg_count != native.arity() {
+                    return Err(self.arity_error(native.arity(), arg_count));
+                }
+
+                let arguments_start = self.stack.len() - arg_count;
+                let arguments = self.stack[arguments_start..]
+                    .iter()
+                    .map(|&argument| self.native_argument(argument))
+                    .collect::<RunResult<Vec<interpret::Value>>>()?;
+
+                let result = native.call(&arguments).map_err(|message| self.error(&Code::NATIVE_ERROR, &message))?;
+                // The arguments and the native stay rooted while the result
+                // is allocated.
+                let result = self.native_result(result)?;
+
+                self.stack.truncate(arguments_start - 1);
+                self.push(result);
+
+                Ok(())
+            }
+            _ => Err(self.error(&Code::NOT_CALLABLE, "Can only call functions and classes.")),
+        }
+    }
+
+    /// Pushes a frame running `closure`, whose arguments are on the stack.
+    fn call(&mut self, closure: ObjRef, arg_count: usize) -> RunResult<()> {
+        let function = self.heap.function(self.heap.closure(closure).function);
+
+        if arg_count != function.arity {
+            return Err(self.arity_error(function.arity, arg_count));
+        }
+
+        if self.frames.len() + 1 >= FRAMES_MAX {
+            return Err(self.error(&Code::STACK_OVERFLOW, "Stack overflow."));
+        }
+
+        let frame = CallFrame {
+            closure,
+            chunk: function.chunk.clone(),
+            ip: 0,
+            slots: self.stack.len() - arg_count - 1,
+        };
+
+        if let Some(caller) = self.frame.replace(frame) {
+            self.frames.push(caller);
+        }
+
+        Ok(())
+    }
+
+    /// Calls the method `name` on the receiver below the arguments, in one
+    /// step instead of `GetProperty` followed by `Call`.
+    fn invoke(&mut self, name: ObjRef, arg_count: usize) -> RunResult<()> {
+        let Some(instance) = self.as_instance(self.peek(arg_count)) else {
+            return Err(self.error(&Code::NOT_AN_INSTANCE, "Only instances have properties."));
+        };
+
+        // A field holding a function shadows any method.
+        if let Some(&value) = self.heap.instance(instance).fields.get(&name) {
+            let receiver_slot = self.stack.len() - arg_count - 1;
+            self.stack[receiver_slot] = value;
+
+            return self.call_value(value, arg_count);
+        }
+
+        let class = self.heap.instance(instance).class;
+        self.invoke_from_class(class, name, arg_count)
+    }
+
+    fn invoke_from_class(&mut self, class: ObjRef, name: ObjRef, arg_count: usize) -> RunResult<()> {
+        match self.heap.class(class).methods.get(&name) {
+            Some(&method) => self.call(method, arg_count),
+            None => Err(self.undefined_property(name)),
+        }
+    }
+
+    /// Replaces the instance on top of the stack with its method `name`,
+    /// bound to it.
+    fn bind_method(&mut self, class: ObjRef, name: ObjRef) -> RunResult<()> {
+        let Some(&method) = self.heap.class(class).methods.get(&name) else {
+            return Err(self.undefined_property(name));
+        };
+
+        let bound = self.alloc(Object::BoundMethod(ObjBoundMethod {
+            receiver: self.peek(0),
+            method,
+        }));
+        self.pop();
+        self.push(Value::Obj(bound));
+
+        Ok(())
+    }
+
+    // Upvalues.
+
+    /// The upvalue for stack slot `slot`, shared with any other closure
+    /// that already captured it.
+    fn capture_upvalue(&mut self, slot: usize) -> ObjRef {
+        let position = self
+            .open_upvalues
+            .partition_point(|&upvalue| self.open_slot(upvalue) < slot);
+
+        if let Some(&upvalue) = self.open_upvalues.get(position) {
+            if self.open_slot(upvalue) == slot {
+                return upvalue;
+            }
+        }
+
+        let upvalue = self.alloc(Object::Upvalue(ObjUpvalue::Open(slot)));
+        self.open_upvalues.insert(position, upvalue);
+
+        upvalue
+    }
+
+    /// Moves the variables in stack slots `last` and above into their
+    /// upvalues, as they are about to be popped.
+    fn close_upvalues(&mut self, last: usize) {
+        while let Some(&upvalue) = self.open_upvalues.last() {
+            let slot = self.open_slot(upvalue);
+            if slot < last {
+                break;
+            }
+
+            *self.heap.upvalue_mut(upvalue) = ObjUpvalue::Closed(self.stack[slot]);
+            self.open_upvalues.pop();
+        }
+    }
+
+    fn open_slot(&self, upvalue: ObjRef) -> usize {
+        match self.heap.upvalue(upvalue) {
+            ObjUpvalue::Open(slot) => *slot,
+            ObjUpvalue::Closed(_) => unreachable!("closed upvalues are not kept open"),
+        }
+    }
+
+    // Garbage collection.
+
+    /// Allocates `object`, collecting garbage first if it's due. Anything
+    /// the VM still needs must be reachable from its roots by then.
+    fn alloc(&mut self, object: Object) -> ObjRef {
+        self.collect_if_due();
+        self.heap.alloc(object)
+    }
+
+    fn intern(&mut self, value: &str) -> ObjRef {
+        self.collect_if_due();
+        self.heap.intern(value)
+    }
+
+    fn collect_if_due(&mut self) {
+        if self.gc_stress || self.heap.should_collect() {
+            self.collect_garbage();
+        }
+    }
+
+    fn collect_garbage(&mut self) {
+        let before = self.heap.bytes_allocated();
+
+        self.mark_roots();
+        let freed = self.heap.collect();
+
+        if self.gc_log {
+            eprintln!(
+                "-- gc collected {} bytes (from {} to {}) next at {}",
+                freed,
+                before,
+                self.heap.bytes_allocated(),
+                self.heap.next_gc()
+            );
+        }
+    }
+
+    fn mark_roots(&mut self) {
+        for &value in &self.stack {
+            self.heap.mark_value(value);
+        }
+
+        for frame in self.frame.iter().chain(&self.frames) {
+            self.heap.mark_object(frame.closure);
+        }
+
+        for &upvalue in &self.open_upvalues {
+            self.heap.mark_object(upvalue);
+        }
+
+        for (&name, &value) in &self.globals {
+            self.heap.mark_object(name);
+            self.heap.mark_value(value);
+        }
+
+        self.heap.mark_object(self.init_string);
+    }
+
+    // Instruction decoding.
+
+    fn frame(&self) -> &CallFrame {
+        self.frame.as_ref().expect("the VM is running a frame")
+    }
+
+    fn frame_mut(&mut self) -> &mut CallFrame {
+        self.frame.as_mut().expect("the VM is running a frame")
+    }
+
+    fn read_byte(&mut self) -> u8 {
+        let frame = self.frame_mut();
+        let byte = frame.chunk.code()[frame.ip];
+        frame.ip += 1;
+
+        byte
+    }
+
+    fn read_short(&mut self) -> u16 {
+        let high = self.read_byte() as u16;
+        let low = self.read_byte() as u16;
+
+        (high << 8) | low
+    }
+
+    fn read_constant(&mut self) -> Value {
+        let index = self.read_byte() as usize;
+
+        self.frame().chunk.constants()[index]
+    }
+
+    fn read_string(&mut self) -> ObjRef {
+        match self.read_constant() {
+            Value::Obj(string) => string,
+            _ => unreachable!("names are string constants"),
+        }
+    }
+
+    // Stack and values.
+
+    fn push(&mut self, value: Value) {
+        self.stack.push(value);
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("the compiler keeps the stack balanced")
+    }
+
+    fn peek(&self, distance: usize) -> Value {
+        self.stack[self.stack.len() - 1 - distance]
+    }
+
+    fn binary_op(&mut self, op: fn(f64, f64) -> Value) -> RunResult<()> {
+        let (Value::Number(left), Value::Number(right)) = (self.peek(1), self.peek(0)) else {
+            return Err(self.error(&Code::OPERANDS_NOT_NUMBERS, "Operands must be numbers."));
+        };
+
+        self.pop();
+        self.pop();
+        self.push(op(left, right));
+
+        Ok(())
+    }
+
+    fn is_string(&self, object: ObjRef) -> bool {
+        matches!(self.heap.get(object), Object::String(_))
+    }
+
+    fn as_instance(&self, value: Value) -> Option<ObjRef> {
+        match value {
+            Value::Obj(object) if matches!(self.heap.get(object), Object::Instance(_)) => Some(object),
+            _ => None,
+        }
+    }
+
+    fn as_class(&self, value: Value) -> Option<ObjRef> {
+        match value {
+            Value::Obj(object) if matches!(self.heap.get(object), Object::Class(_)) => Some(object),
+            _ => None,
+        }
+    }
+
+    // Errors, reported on the line of the instruction being executed.
+
+    fn error(&self, code: &'static Code, message: &str) -> RuntimeError {
+        let frame = self.frame();
+        let offset = frame.ip - 1;
+
+        RuntimeError::new(code, frame.chunk.line(offset), frame.chunk.span(offset), message.to_string())
+    }
+
+    /// `value` as the tree-walk interpreter's value, which natives take.
+    fn native_argument(&self, value: Value) -> RunResult<interpret::Value> {
+        match value {
+            Value::Nil => Ok(interpret::Value::Nil),
+            Value::Bool(boolean) => Ok(interpret::Value::Bool(boolean)),
+            Value::Number(number) => Ok(interpret::Value::Number(number)),
+            Value::Obj(object) => match self.heap.get(object) {
+                Object::String(string) => Ok(interpret::Value::String(string.clone())),
+                Object::Native(native) => Ok(interpret::Value::Native(native.function.clone())),
+                _ => Err(self.error(&Code::NATIVE_ERROR, NATIVE_VALUES)),
+            },
+        }
+    }
+
+    /// The value a native returned, as a value of this VM.
+    fn native_result(&mut self, value: interpret::Value) -> RunResult<Value> {
+        match value {
+            interpret::Value::Nil => Ok(Value::Nil),
+            interpret::Value::Bool(boolean) => Ok(Value::Bool(boolean)),
+            interpret::Value::Number(number) => Ok(Value::Number(number)),
+            interpret::Value::String(string) => Ok(Value::Obj(self.intern(&string))),
+            interpret::Value::Native(function) => Ok(Value::Obj(self.alloc(Object::Native(ObjNative { function })))),
+            _ => Err(self.error(&Code::NATIVE_ERROR, NATIVE_VALUES)),
+        }
+    }
+
+    fn arity_error(&self, arity: usize, arg_count: usize) -> RuntimeError {
+        self.error(&Code::ARITY_MISMATCH, &format!("Expected {} arguments but got {}.", arity, arg_count))
+    }
+
+    fn undefined_variable(&self, name: ObjRef) -> RuntimeError {
+        self.error(&Code::UNDEFINED_VARIABLE, &format!("Undefined variable '{}'.", self.heap.string(name)))
+    }
+
+    fn undefined_property(&self, name: ObjRef) -> RuntimeError {
+        self.error(&Code::UNDEFINED_PROPERTY, &format!("Undefined property '{}'.", self.heap.string(name)))
+    }
+}
+
+impl Default for Vm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
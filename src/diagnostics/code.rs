@@ -0,0 +1,101 @@
+use super::{Phase, Severity, CODES};
+
+use std::fmt;
+
+/// A stable identifier for a kind of diagnostic, like
+/// `L0001 unexpected-character`. Ids and names never change meaning once
+/// released, so they can be searched for and referred to. Each code is
+/// reported with one severity.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Code {
+    id: &'static str,
+    name: &'static str,
+    phase: Phase,
+    severity: Severity,
+    explanation: &'static str,
+}
+
+impl Code {
+    /// The code of an error.
+    pub const fn new(
+        id: &'static str,
+        name: &'static str,
+        phase: Phase,
+        explanation: &'static str,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            phase,
+            severity: Severity::Error,
+            explanation,
+        }
+    }
+
+    /// The code of a warning, which can be allowed with `--allow`.
+    pub const fn warning(
+        id: &'static str,
+        name: &'static str,
+        phase: Phase,
+        explanation: &'static str,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            phase,
+            severity: Severity::Warning,
+            explanation,
+        }
+    }
+
+    /// The code of a note, which points out something worth a look
+    /// without being wrong. Notes can be allowed like warnings.
+    pub const fn note(
+        id: &'static str,
+        name: &'static str,
+        phase: Phase,
+        explanation: &'static str,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            phase,
+            severity: Severity::Note,
+            explanation,
+        }
+    }
+
+    pub fn id(&self) -> &'static str {
+        self.id
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// The long-form description `explain` prints.
+    pub fn explanation(&self) -> &'static str {
+        self.explanation
+    }
+
+    /// The code with the id, in any case, or the name `query`.
+    pub fn lookup(query: &str) -> Option<&'static Code> {
+        CODES
+            .iter()
+            .find(|code| code.id.eq_ignore_ascii_case(query) || code.name == query)
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.id, self.name)
+    }
+}
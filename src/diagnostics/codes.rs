@@ -0,0 +1,430 @@
+use super::{Code, Phase};
+
+/// The code of every kind of diagnostic, by phase.
+impl Code {
+    // Lexer.
+    pub const UNEXPECTED_CHARACTER: Code = Code::new(
+        "L0001",
+        "unexpected-character",
+        Phase::Lexer,
+        "A character that no Lox token starts with appeared outside of a string
+or comment. Lox has no `$`, `#` or `@` operators, for instance.
+
+    print 1 # 2;
+
+The rest of the line is still scanned, so further errors are reported too.",
+    );
+
+    pub const UNTERMINATED_STRING: Code = Code::new(
+        "L0002",
+        "unterminated-string",
+        Phase::Lexer,
+        "A string literal was still open at the end of the file. Strings may
+span several lines, so the missing `\"` can be far from where the string
+starts.
+
+    print \"hello;",
+    );
+
+    pub const UNTERMINATED_BLOCK_COMMENT: Code = Code::new(
+        "L0003",
+        "unterminated-block-comment",
+        Phase::Lexer,
+        "A `/*` comment was still open at the end of the file. Block comments
+nest, so each `/*` inside one needs its own `*/`.
+
+    /* outer /* inner */
+    print 1;",
+    );
+
+    pub const INVALID_ESCAPE_SEQUENCE: Code = Code::new(
+        "L0004",
+        "invalid-escape-sequence",
+        Phase::Lexer,
+        "A backslash in a string was followed by a character that doesn't
+make an escape sequence. The valid ones are `\\n`, `\\t`, `\\r`, `\\\\`,
+`\\\"` and `\\u{...}`.
+
+    print \"C:\\temp\\q\";",
+    );
+
+    pub const INVALID_UNICODE_ESCAPE: Code = Code::new(
+        "L0005",
+        "invalid-unicode-escape",
+        Phase::Lexer,
+        "A `\\u{...}` escape didn't hold the hexadecimal code of a Unicode
+scalar value: one to six hex digits, not naming a surrogate.
+
+    print \"\\u{110000}\";",
+    );
+
+    pub const MALFORMED_NUMBER: Code = Code::new(
+        "L0006",
+        "malformed-number",
+        Phase::Lexer,
+        "A number literal doesn't follow the number syntax. Underscores may
+only separate digits, one at a time, and `0x`, `0b` and an exponent's `e`
+need at least one digit after them.
+
+    print 1__000;
+    print 0x;",
+    );
+
+    // Parser, and the bytecode compiler limits it checks while parsing.
+    pub const EXPECTED_EXPRESSION: Code = Code::new(
+        "L0101",
+        "expected-expression",
+        Phase::Parser,
+        "An expression was needed, but the token found can't start one. This
+is often an operator missing its operand.
+
+    print 1 + ;",
+    );
+
+    pub const EXPECTED_TOKEN: Code = Code::new(
+        "L0102",
+        "expected-token",
+        Phase::Parser,
+        "The grammar requires a specific token here, named in the message,
+such as the `;` ending a statement or the `)` closing a call.
+
+    var a = 1
+    print a;",
+    );
+
+    pub const INVALID_ASSIGNMENT_TARGET: Code = Code::new(
+        "L0103",
+        "invalid-assignment-target",
+        Phase::Parser,
+        "The left-hand side of `=` is not a variable or a property, so there
+is nothing to assign to.
+
+    1 + 2 = 3;",
+    );
+
+    pub const TOO_MANY_PARAMETERS: Code = Code::new(
+        "L0104",
+        "too-many-parameters",
+        Phase::Parser,
+        "Functions and methods take at most 255 parameters.",
+    );
+
+    pub const TOO_MANY_ARGUMENTS: Code = Code::new(
+        "L0105",
+        "too-many-arguments",
+        Phase::Parser,
+        "Calls pass at most 255 arguments.",
+    );
+
+    pub const TOO_MANY_CONSTANTS: Code = Code::new(
+        "L0106",
+        "too-many-constants",
+        Phase::Parser,
+        "The bytecode VM keeps at most 256 distinct constants, names and
+literals, per function. Split the function up.",
+    );
+
+    pub const TOO_MANY_LOCALS: Code = Code::new(
+        "L0107",
+        "too-many-locals",
+        Phase::Parser,
+        "The bytecode VM allows at most 256 local variables in scope at once
+in a function, parameters included.",
+    );
+
+    pub const TOO_MANY_UPVALUES: Code = Code::new(
+        "L0108",
+        "too-many-upvalues",
+        Phase::Parser,
+        "The bytecode VM lets a function capture at most 256 variables from
+the functions around it.",
+    );
+
+    pub const JUMP_TOO_LARGE: Code = Code::new(
+        "L0109",
+        "jump-too-large",
+        Phase::Parser,
+        "The bytecode of an `if` branch or a loop body is too long for the
+VM's jump instructions, which span at most 65535 bytes. Move some of it
+into functions.",
+    );
+
+    // Resolver.
+    pub const SELF_REFERENCING_INITIALIZER: Code = Code::new(
+        "L0201",
+        "self-referencing-initializer",
+        Phase::Resolver,
+        "A local variable was read in its own initializer. Inside a block the
+new variable already shadows any outer one of the same name, but has no
+value yet.
+
+    var a = 1;
+    {
+      var a = a + 1;
+    }",
+    );
+
+    pub const DUPLICATE_DECLARATION: Code = Code::new(
+        "L0202",
+        "duplicate-declaration",
+        Phase::Resolver,
+        "A local scope declared the same name twice, which is most likely a
+mistake. Globals may be redeclared.
+
+    fun f(a) {
+      var a = 2;
+    }",
+    );
+
+    pub const TOP_LEVEL_RETURN: Code = Code::new(
+        "L0203",
+        "top-level-return",
+        Phase::Resolver,
+        "`return` appeared outside of any function or method.
+
+    return 1;",
+    );
+
+    pub const INITIALIZER_RETURN_VALUE: Code = Code::new(
+        "L0204",
+        "initializer-return-value",
+        Phase::Resolver,
+        "An `init` method returned a value. Initializers always return the
+instance, so they may only use a bare `return;`.
+
+    class A {
+      init() { return 1; }
+    }",
+    );
+
+    pub const THIS_OUTSIDE_CLASS: Code = Code::new(
+        "L0205",
+        "this-outside-class",
+        Phase::Resolver,
+        "`this` appeared outside of any method, where there is no instance
+for it to refer to.
+
+    print this;",
+    );
+
+    pub const SUPER_OUTSIDE_CLASS: Code = Code::new(
+        "L0206",
+        "super-outside-class",
+        Phase::Resolver,
+        "`super` appeared outside of any method.
+
+    super.method();",
+    );
+
+    pub const SUPER_WITHOUT_SUPERCLASS: Code = Code::new(
+        "L0207",
+        "super-without-superclass",
+        Phase::Resolver,
+        "`super` appeared in a method of a class that doesn't inherit from
+another, so there are no superclass methods to call.
+
+    class A {
+      method() { super.method(); }
+    }",
+    );
+
+    pub const SELF_INHERITANCE: Code = Code::new(
+        "L0208",
+        "self-inheritance",
+        Phase::Resolver,
+        "A class named itself as its superclass.
+
+    class A < A {}",
+    );
+
+    pub const UNUSED_VARIABLE: Code = Code::warning(
+        "L0209",
+        "unused-variable",
+        Phase::Resolver,
+        "A local variable declared with `var` is never read or assigned, so it
+is either left over or misspelled where it was meant to be used. Names
+starting with `_` are exempt, for variables kept on purpose. Warnings
+are reported by `run` with `--diagnostics=pretty` or `--format=json`, and
+`--allow=unused-variable` turns this one off.
+
+    fun area(width, height) {
+      var perimeter = 2 * (width + height);
+      return width * height;
+    }",
+    );
+
+    pub const SHADOWED_VARIABLE: Code = Code::note(
+        "L0210",
+        "shadowed-variable",
+        Phase::Resolver,
+        "A local variable has the same name as a local of an enclosing scope,
+which it hides until the end of its own scope. This is legal, but the
+outer variable may have been meant. Like warnings, notes are reported by
+`run` with `--diagnostics=pretty` or `--format=json`, and
+`--allow=shadowed-variable` turns this one off.
+
+    fun greet(name) {
+      if (name == nil) {
+        var name = \"world\";
+        print \"Hello, \" + name;
+      }
+    }",
+    );
+
+    // Runtime.
+    pub const NATIVE_ERROR: Code = Code::new(
+        "L0300",
+        "native-error",
+        Phase::Runtime,
+        "A native function, one the program embedding the interpreter defined,
+reported an error. The message is the native's own. The bytecode VM also
+reports this when a native gets or returns a value it can't pass between
+Lox and Rust, such as an instance.",
+    );
+
+    pub const OPERAND_NOT_NUMBER: Code = Code::new(
+        "L0301",
+        "operand-not-number",
+        Phase::Runtime,
+        "Unary `-` was applied to a value that is not a number.
+
+    print -\"one\";",
+    );
+
+    pub const OPERANDS_NOT_NUMBERS: Code = Code::new(
+        "L0302",
+        "operands-not-numbers",
+        Phase::Runtime,
+        "An arithmetic or comparison operator other than `+` got an operand
+that is not a number. Lox never converts values implicitly.
+
+    print \"2\" * 3;",
+    );
+
+    pub const INVALID_ADDITION: Code = Code::new(
+        "L0303",
+        "invalid-addition",
+        Phase::Runtime,
+        "`+` adds two numbers or concatenates two strings, and nothing else.
+Mixing a string and a number is an error rather than a conversion.
+
+    print \"total: \" + 3;",
+    );
+
+    pub const UNDEFINED_VARIABLE: Code = Code::new(
+        "L0304",
+        "undefined-variable",
+        Phase::Runtime,
+        "A global variable was read or assigned before any `var` declared it.
+Globals are looked up when the code runs, so a function may refer to a
+global declared after it, as long as it is only called afterwards.
+
+    print count;
+    var count = 1;",
+    );
+
+    pub const UNDEFINED_PROPERTY: Code = Code::new(
+        "L0305",
+        "undefined-property",
+        Phase::Runtime,
+        "An instance has neither a field nor a method with the name accessed.
+Fields only exist once assigned.
+
+    class Point {}
+    print Point().x;",
+    );
+
+    pub const NOT_CALLABLE: Code = Code::new(
+        "L0306",
+        "not-callable",
+        Phase::Runtime,
+        "A value that is not a function, method or class was called.
+
+    var name = \"lox\";
+    name();",
+    );
+
+    pub const ARITY_MISMATCH: Code = Code::new(
+        "L0307",
+        "arity-mismatch",
+        Phase::Runtime,
+        "A function was called with a different number of arguments than it
+has parameters. Lox has no optional or variadic parameters. Calling a
+class passes the arguments to its `init` method.
+
+    fun add(a, b) { return a + b; }
+    add(1);",
+    );
+
+    pub const STACK_OVERFLOW: Code = Code::new(
+        "L0308",
+        "stack-overflow",
+        Phase::Runtime,
+        "Calls nested deeper than the interpreter allows, almost always
+because of a recursion that never reaches its base case.
+
+    fun loop() { return loop(); }
+    loop();",
+    );
+
+    pub const NOT_AN_INSTANCE: Code = Code::new(
+        "L0309",
+        "not-an-instance",
+        Phase::Runtime,
+        "A property was read from or written to a value that is not an
+instance. Only instances of classes have fields and methods.
+
+    var n = 1;
+    n.size = 2;",
+    );
+
+    pub const SUPERCLASS_NOT_CLASS: Code = Code::new(
+        "L0310",
+        "superclass-not-class",
+        Phase::Runtime,
+        "The value after `<` in a class declaration is not a class.
+
+    var Base = \"base\";
+    class A < Base {}",
+    );
+}
+
+/// Every code, in id order, for `explain`.
+pub const CODES: &[Code] = &[
+    Code::UNEXPECTED_CHARACTER,
+    Code::UNTERMINATED_STRING,
+    Code::UNTERMINATED_BLOCK_COMMENT,
+    Code::INVALID_ESCAPE_SEQUENCE,
+    Code::INVALID_UNICODE_ESCAPE,
+    Code::MALFORMED_NUMBER,
+    Code::EXPECTED_EXPRESSION,
+    Code::EXPECTED_TOKEN,
+    Code::INVALID_ASSIGNMENT_TARGET,
+    Code::TOO_MANY_PARAMETERS,
+    Code::TOO_MANY_ARGUMENTS,
+    Code::TOO_MANY_CONSTANTS,
+    Code::TOO_MANY_LOCALS,
+    Code::TOO_MANY_UPVALUES,
+    Code::JUMP_TOO_LARGE,
+    Code::SELF_REFERENCING_INITIALIZER,
+    Code::DUPLICATE_DECLARATION,
+    Code::TOP_LEVEL_RETURN,
+    Code::INITIALIZER_RETURN_VALUE,
+    Code::THIS_OUTSIDE_CLASS,
+    Code::SUPER_OUTSIDE_CLASS,
+    Code::SUPER_WITHOUT_SUPERCLASS,
+    Code::SELF_INHERITANCE,
+    Code::UNUSED_VARIABLE,
+    Code::SHADOWED_VARIABLE,
+    Code::NATIVE_ERROR,
+    Code::OPERAND_NOT_NUMBER,
+    Code::OPERANDS_NOT_NUMBERS,
+    Code::INVALID_ADDITION,
+    Code::UNDEFINED_VARIABLE,
+    Code::UNDEFINED_PROPERTY,
+    Code::NOT_CALLABLE,
+    Code::ARITY_MISMATCH,
+    Code::STACK_OVERFLOW,
+    Code::NOT_AN_INSTANCE,
+    Code::SUPERCLASS_NOT_CLASS,
+];
@@ -0,0 +1,212 @@
+use crate::bytecode::CompileError;
+use crate::interpret::{RuntimeError, MAX_CALL_DEPTH};
+use crate::parse::ParseError;
+use crate::resolve::{ResolveError, ResolveWarning};
+use crate::tokenize::{Span, TokenizerError};
+use super::{Code, Label, Severity};
+
+/// A problem found in a program, by any phase: a message with a severity
+/// and a stable `Code`, about a line of source and usually a span on it.
+/// The primary label may say what is wrong at that span, secondary labels
+/// point at related places, notes give context and the help text suggests
+/// a fix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    severity: Severity,
+    code: &'static Code,
+    message: String,
+    line: u32,
+    // `None` when only the line is known.
+    primary: Option<Label>,
+    secondary: Vec<Label>,
+    notes: Vec<String>,
+    help: Option<String>,
+}
+
+impl Diagnostic {
+    /// A diagnostic about `line`, with the severity of its code and
+    /// without a span yet.
+    pub fn new(code: &'static Code, line: u32, message: String) -> Self {
+        Self {
+            severity: code.severity(),
+            code,
+            message,
+            line,
+            primary: None,
+            secondary: Vec::new(),
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+
+    /// Points the diagnostic at `span`, without any label text yet.
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.primary = Some(Label::new(span, String::new()));
+        self
+    }
+
+    /// Sets the text of the primary label, which needs a span.
+    pub fn with_label(mut self, text: String) -> Self {
+        self.primary = self.primary.map(|primary| Label::new(primary.span(), text));
+        self
+    }
+
+    pub fn with_secondary(mut self, span: Span, text: String) -> Self {
+        self.secondary.push(Label::new(span, text));
+        self
+    }
+
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Self {
+        self.help = Some(help);
+        self
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn code(&self) -> &'static Code {
+        self.code
+    }
+
+    /// The line reported in plain output, the span's or, for a string
+    /// running over several lines, the one it ends on.
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// `None` when only the line is known.
+    pub fn span(&self) -> Option<Span> {
+        self.primary.as_ref().map(Label::span)
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The text of the primary label, `None` when it has none.
+    pub fn label(&self) -> Option<&str> {
+        self.primary.as_ref().map(Label::text).filter(|text| !text.is_empty())
+    }
+
+    pub fn secondary(&self) -> &[Label] {
+        &self.secondary
+    }
+
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+
+    pub fn help(&self) -> Option<&str> {
+        self.help.as_deref()
+    }
+
+    /// A diagnostic for one of the errors or warnings below, with the
+    /// notes and help known for its code.
+    fn annotated(code: &'static Code, line: u32, span: Option<Span>, message: &str) -> Self {
+        let mut diagnostic = Self::new(code, line, message.to_string());
+
+        if let Some(span) = span {
+            diagnostic = diagnostic.with_span(span);
+        }
+
+        if let Some(note) = note_for(code) {
+            diagnostic = diagnostic.with_note(note);
+        }
+        if let Some(help) = help_for(code) {
+            diagnostic = diagnostic.with_help(help.to_string());
+        }
+
+        diagnostic
+    }
+}
+
+impl From<&TokenizerError> for Diagnostic {
+    fn from(token_error: &TokenizerError) -> Self {
+        Self::annotated(token_error.code(), token_error.line(), token_error.span(), token_error.message())
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(parse_error: &ParseError) -> Self {
+        let diagnostic = Self::annotated(parse_error.code(), parse_error.line(), parse_error.span(), parse_error.message());
+
+        match parse_error.lexeme() {
+            Some(lexeme) => diagnostic.with_label(format!("found `{}`", lexeme)),
+            None => diagnostic.with_label("found the end of the file".to_string()),
+        }
+    }
+}
+
+impl From<&ResolveError> for Diagnostic {
+    fn from(resolve_error: &ResolveError) -> Self {
+        let diagnostic = Self::annotated(resolve_error.code(), resolve_error.line(), resolve_error.span(), resolve_error.message());
+
+        match resolve_error.first_declaration() {
+            Some(first_declaration) => diagnostic
+                .with_label("declared again here".to_string())
+                .with_secondary(first_declaration, "first declared here".to_string()),
+            None => diagnostic,
+        }
+    }
+}
+
+impl From<&ResolveWarning> for Diagnostic {
+    fn from(resolve_warning: &ResolveWarning) -> Self {
+        let diagnostic = Self::annotated(resolve_warning.code(), resolve_warning.line(), resolve_warning.span(), resolve_warning.message());
+
+        match resolve_warning.shadowed_declaration() {
+            Some(shadowed_declaration) => diagnostic.with_secondary(shadowed_declaration, "shadowed variable declared here".to_string()),
+            None => diagnostic,
+        }
+    }
+}
+
+impl From<&CompileError> for Diagnostic {
+    fn from(compile_error: &CompileError) -> Self {
+        match compile_error {
+            CompileError::Parse(parse_error) => parse_error.into(),
+            CompileError::Resolve(resolve_error) => resolve_error.into(),
+        }
+    }
+}
+
+impl From<&RuntimeError> for Diagnostic {
+    fn from(runtime_error: &RuntimeError) -> Self {
+        Self::annotated(runtime_error.code(), runtime_error.line(), runtime_error.span(), runtime_error.message())
+    }
+}
+
+/// Context for the codes that need some.
+fn note_for(code: &Code) -> Option<String> {
+    let note = match *code {
+        Code::TOP_LEVEL_RETURN => "`return` is only allowed in functions and methods".to_string(),
+        Code::INITIALIZER_RETURN_VALUE => "`init` always returns `this`".to_string(),
+        Code::INVALID_ADDITION => "`+` adds numbers or concatenates strings, it doesn't convert between them".to_string(),
+        Code::STACK_OVERFLOW => format!("calls can only nest {} deep", MAX_CALL_DEPTH),
+        _ => return None,
+    };
+
+    Some(note)
+}
+
+/// Suggested fixes for the codes that have an obvious one.
+fn help_for(code: &Code) -> Option<&'static str> {
+    let help = match *code {
+        Code::UNTERMINATED_STRING => "close the string with a `\"`",
+        Code::UNTERMINATED_BLOCK_COMMENT => "close the comment with `*/`",
+        Code::SELF_REFERENCING_INITIALIZER => "give the new variable another name to read the outer one",
+        Code::SUPER_WITHOUT_SUPERCLASS => "inherit from a class with `class Name < Superclass`",
+        Code::UNUSED_VARIABLE => "remove it, or start its name with `_` to keep it",
+        Code::SHADOWED_VARIABLE => "rename one of them if both are meant to be used",
+        Code::UNDEFINED_VARIABLE => "declare it with `var` before using it",
+        _ => return None,
+    };
+
+    Some(help)
+}
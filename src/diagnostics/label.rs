@@ -0,0 +1,25 @@
+use crate::tokenize::Span;
+
+/// A short text attached to a span of source, shown next to its underline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    span: Span,
+    text: String,
+}
+
+impl Label {
+    pub fn new(span: Span, text: String) -> Self {
+        Self {
+            span,
+            text,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
@@ -0,0 +1,236 @@
+mod phase;
+pub use phase::Phase;
+
+mod severity;
+pub use severity::Severity;
+
+mod code;
+pub use code::Code;
+
+mod codes;
+pub use codes::CODES;
+
+mod label;
+pub use label::Label;
+
+mod diagnostic;
+pub use diagnostic::Diagnostic;
+
+mod renderer;
+pub use renderer::Renderer;
+
+#[cfg(test)]
+use crate::parse::parse;
+#[cfg(test)]
+use crate::resolve::{resolve, resolve_with_warnings};
+#[cfg(test)]
+use crate::tokenize::tokenize;
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+/// The diagnostic for the first error found in `source`, whatever stage
+/// it comes from.
+#[cfg(test)]
+fn first_diagnostic(source: &str) -> Option<Diagnostic> {
+    let (tokens, token_errors) = tokenize(source);
+    if let Some(token_error) = token_errors.first() {
+        return Some(token_error.into());
+    }
+
+    let statements = match parse(tokens) {
+        Ok(statements) => statements,
+        Err(parse_errors) => return Some((&parse_errors[0]).into()),
+    };
+
+    resolve(&statements)
+        .err()
+        .map(|resolve_errors| (&resolve_errors[0]).into())
+}
+
+#[cfg(test)]
+fn render_first_error(source: &str) -> String {
+    first_diagnostic(source)
+        .map(|diagnostic| Renderer::new("test.lox", source).render(&diagnostic))
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_render_spans() {
+    assert_eq!(
+        render_first_error("var a = 1;\nprint a @ 2;\n"),
+        "\
+error[L0001]: Unexpected character: @
+ --> test.lox:2:9
+  |
+2 | print a @ 2;
+  |         ^
+"
+    );
+
+    assert_eq!(
+        render_first_error("print (1 +\n  2 \"two\");"),
+        "\
+error[L0102]: Expect ')' after expression.
+ --> test.lox:2:5
+  |
+2 |   2 \"two\");
+  |     ^~~~~ found `\"two\"`
+"
+    );
+
+    assert_eq!(
+        render_first_error("fun f() {\n\tvar x = 1;\n\t{ var x = x; }\n}"),
+        "\
+error[L0201]: Can't read local variable in its own initializer.
+ --> test.lox:3:12
+  |
+3 | \t{ var x = x; }
+  | \t          ^
+  |
+  = help: give the new variable another name to read the outer one
+"
+    );
+}
+
+#[test]
+fn test_render_notes_and_multiline_spans() {
+    assert_eq!(
+        render_first_error("print \"never\nclosed;"),
+        "\
+error[L0002]: Unterminated string.
+ --> test.lox:1:7
+  |
+1 | print \"never
+  |       ^~~~~~
+  |
+  = help: close the string with a `\"`
+"
+    );
+
+    assert_eq!(
+        render_first_error("print 1 +"),
+        "\
+error[L0101]: Expect expression.
+ --> test.lox:1:10
+  |
+1 | print 1 +
+  |          ^ found the end of the file
+"
+    );
+}
+
+#[test]
+fn test_render_line_only_spans_and_colour() {
+    use crate::tokenize::TokenizerError;
+
+    // Errors built without a source only know their line.
+    let source = "var a;\nprint \"a;\n";
+    let token_error = TokenizerError::new(&Code::UNTERMINATED_STRING, 2, "Unterminated string.".to_string());
+
+    assert_eq!(
+        Renderer::new("test.lox", source).render(&(&token_error).into()),
+        "\
+error[L0002]: Unterminated string.
+ --> test.lox:2
+  |
+2 | print \"a;
+  |
+  = help: close the string with a `\"`
+"
+    );
+
+    assert_eq!(
+        Renderer::new("test.lox", source).with_colour().render(&(&token_error).into()),
+        "\
+\x1b[1;31merror[L0002]\x1b[0m\x1b[1m: Unterminated string.\x1b[0m
+ \x1b[1;34m-->\x1b[0m test.lox:2
+  \x1b[1;34m|\x1b[0m
+\x1b[1;34m2 |\x1b[0m print \"a;
+  \x1b[1;34m|\x1b[0m
+  \x1b[1;34m=\x1b[0m \x1b[1mhelp\x1b[0m: close the string with a `\"`
+"
+    );
+
+    // An empty span at the very start of the file still gets its caret.
+    let parse_error = crate::parse::parse_expression(tokenize("").0).unwrap_err();
+    assert_eq!(
+        Renderer::new("test.lox", "").render(&(&parse_error).into()),
+        "\
+error[L0101]: Expect expression.
+ --> test.lox:1:1
+  |
+1 | 
+  | ^ found the end of the file
+"
+    );
+}
+
+#[test]
+fn test_render_secondary_labels() {
+    assert_eq!(
+        render_first_error("fun f(a) {\n  var b = a;\n  var a = 2;\n}\n"),
+        "\
+error[L0202]: Already a variable with this name in this scope.
+ --> test.lox:3:7
+  |
+3 |   var a = 2;
+  |       ^ declared again here
+1 | fun f(a) {
+  |       - first declared here
+"
+    );
+}
+
+#[test]
+fn test_render_warnings() {
+    let source = "{\n  var a = 1;\n  var _b = 2;\n  var c = 3;\n  print c;\n}\n";
+    let (tokens, _) = tokenize(source);
+    let (_, warnings) = resolve_with_warnings(&parse(tokens).unwrap()).unwrap();
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        Renderer::new("test.lox", source).render(&(&warnings[0]).into()),
+        "\
+warning[L0209]: Local variable 'a' is never used.
+ --> test.lox:2:7
+  |
+2 |   var a = 1;
+  |       ^
+  |
+  = help: remove it, or start its name with `_` to keep it
+"
+    );
+}
+
+#[test]
+fn test_codes_are_unique() {
+    for (index, code) in CODES.iter().enumerate() {
+        for other in &CODES[index + 1..] {
+            assert_ne!(code.id(), other.id());
+            assert_ne!(code.name(), other.name());
+        }
+        assert_eq!(Code::lookup(code.id()), Some(code));
+        assert_eq!(Code::lookup(code.name()), Some(code));
+    }
+
+}
+
+#[test]
+fn test_codes_of_errors() {
+    let code = |source| first_diagnostic(source).unwrap().code().to_string();
+
+    assert_eq!(code("print 1 # 2;"), "L0001 unexpected-character");
+    assert_eq!(code("print 1__000;"), "L0006 malformed-number");
+    assert_eq!(code("print 1 + ;"), "L0101 expected-expression");
+    assert_eq!(code("print 1"), "L0102 expected-token");
+    assert_eq!(code("1 + 2 = 3;"), "L0103 invalid-assignment-target");
+    assert_eq!(code("return 1;"), "L0203 top-level-return");
+    assert_eq!(code("fun f(a) { var a = 2; }"), "L0202 duplicate-declaration");
+}
+
+#[test]
+fn test_codes_lookup() {
+    assert_eq!(Code::lookup("l0002").map(Code::name), Some("unterminated-string"));
+    assert_eq!(Code::lookup("L9999"), None);
+}
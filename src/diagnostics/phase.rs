@@ -0,0 +1,23 @@
+use std::fmt;
+
+/// The stage of running a program that raised a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Lexer,
+    Parser,
+    Resolver,
+    Runtime,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Phase::Lexer => "lexer",
+            Phase::Parser => "parser",
+            Phase::Resolver => "resolver",
+            Phase::Runtime => "runtime",
+        };
+
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,160 @@
+use crate::tokenize::Span;
+use super::{Diagnostic, Severity};
+
+use std::fmt::Write;
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const GREEN: &str = "\x1b[1;32m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders diagnostics about one source file in the style of rustc:
+///
+/// ```text
+/// error[L0101]: Expect expression.
+///  --> test.lox:1:9
+///   |
+/// 1 | print 1 +;
+///   |          ^ found `;`
+/// ```
+///
+/// followed by its secondary labels, notes and help, if any.
+pub struct Renderer<'src> {
+    file_name: &'src str,
+    source: &'src str,
+    colour: bool,
+}
+
+impl<'src> Renderer<'src> {
+    pub fn new(file_name: &'src str, source: &'src str) -> Self {
+        Self {
+            file_name,
+            source,
+            colour: false,
+        }
+    }
+
+    /// Highlights the output with ANSI colours, for terminals.
+    pub fn with_colour(mut self) -> Self {
+        self.colour = true;
+        self
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let span = diagnostic.span();
+        let line = span.map_or(diagnostic.line(), |span| span.start().line());
+
+        let last_line = diagnostic
+            .secondary()
+            .iter()
+            .map(|label| label.span().start().line())
+            .fold(line, u32::max);
+        let width = last_line.to_string().len();
+        let gutter = format!("{:width$} {}", "", self.paint(BLUE, "|"));
+
+        let colour = match diagnostic.severity() {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+            Severity::Note => GREEN,
+        };
+
+        let mut rendered = String::new();
+
+        // Writing to a `String` can't fail.
+        let _ = writeln!(
+            rendered,
+            "{}{}",
+            self.paint(colour, &format!("{}[{}]", diagnostic.severity(), diagnostic.code().id())),
+            self.paint(BOLD, &format!(": {}", diagnostic.message()))
+        );
+        let location = match span {
+            Some(span) => format!("{}:{}:{}", self.file_name, line, span.start().column()),
+            None => format!("{}:{}", self.file_name, line),
+        };
+        let _ = writeln!(rendered, "{:width$}{} {}", "", self.paint(BLUE, "-->"), location);
+        let _ = writeln!(rendered, "{}", gutter);
+
+        // Without a span there is nothing to underline.
+        let source_line = self.source_line(&mut rendered, width, line);
+        if let Some(span) = span {
+            self.mark(&mut rendered, width, source_line, span, diagnostic.label(), (colour, '^', '~'));
+        }
+
+        // Secondary labels each get their own snippet, after the primary.
+        for label in diagnostic.secondary() {
+            let source_line = self.source_line(&mut rendered, width, label.span().start().line());
+            self.mark(&mut rendered, width, source_line, label.span(), Some(label.text()), (BLUE, '-', '-'));
+        }
+
+        if !diagnostic.notes().is_empty() || diagnostic.help().is_some() {
+            let _ = writeln!(rendered, "{}", gutter);
+        }
+        for note in diagnostic.notes() {
+            let _ = writeln!(rendered, "{:width$} {} {}: {}", "", self.paint(BLUE, "="), self.paint(BOLD, "note"), note);
+        }
+        if let Some(help) = diagnostic.help() {
+            let _ = writeln!(rendered, "{:width$} {} {}: {}", "", self.paint(BLUE, "="), self.paint(BOLD, "help"), help);
+        }
+
+        rendered
+    }
+
+    /// Appends line `number` of the source, returning it.
+    fn source_line(&self, rendered: &mut String, width: usize, number: u32) -> &'src str {
+        let line = self.source.lines().nth(number as usize - 1).unwrap_or("");
+        let _ = writeln!(rendered, "{} {}", self.paint(BLUE, &format!("{:>width$} |", number)), line);
+
+        line
+    }
+
+    /// Appends the underline of `span` on `line`, in the given colour and
+    /// characters, followed by `text`.
+    fn mark(
+        &self,
+        rendered: &mut String,
+        width: usize,
+        line: &str,
+        span: Span,
+        text: Option<&str>,
+        (colour, first, rest): (&str, char, char),
+    ) {
+        let underline = match text {
+            Some(text) => format!("{} {}", underline(line, span, first, rest), text),
+            None => underline(line, span, first, rest),
+        };
+        let _ = writeln!(rendered, "{:width$} {} {}", "", self.paint(BLUE, "|"), self.paint(colour, &underline));
+    }
+
+    fn paint(&self, colour: &str, text: &str) -> String {
+        match self.colour {
+            true => format!("{}{}{}", colour, text, RESET),
+            false => text.to_string(),
+        }
+    }
+}
+
+/// Underlines the part of `line` that `span` covers, like `^~~~` with
+/// `first` and `rest` being `^` and `~`. Spans running past the line are
+/// underlined to its end, and empty ones get a lone `first`.
+fn underline(line: &str, span: Span, first: char, rest: char) -> String {
+    let start = span.start().column() as usize - 1;
+    let line_length = line.chars().count();
+
+    let end = match span.end().line() == span.start().line() {
+        true => span.end().column() as usize - 1,
+        false => line_length,
+    };
+    let length = end.saturating_sub(start).max(1);
+
+    // Tabs are kept so the caret lines up however they are displayed.
+    let padding = line
+        .chars()
+        .chain(std::iter::repeat(' '))
+        .take(start)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+
+    format!("{}{}{}", padding, first, rest.to_string().repeat(length - 1))
+}
@@ -0,0 +1,112 @@
+use crate::diagnostics::Code;
+use crate::parse::AstToken;
+use super::{RuntimeError, Value};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A scope of variable bindings, chained to the scope that encloses it.
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
+    /// Binds `name` in this scope, shadowing or redefining any previous
+    /// binding of the same name here.
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// The bindings of this scope alone, in no particular order.
+    pub fn bindings(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.values.iter().map(|(name, value)| (name.as_str(), value))
+    }
+
+    pub fn get(&self, name: &AstToken) -> Result<Value, RuntimeError> {
+        if let Some(value) = self.values.get(name.lexeme()) {
+            return Ok(value.clone());
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().get(name),
+            None => Err(Self::undefined(name)),
+        }
+    }
+
+    pub fn assign(&mut self, name: &AstToken, value: Value) -> Result<(), RuntimeError> {
+        if let Some(slot) = self.values.get_mut(name.lexeme()) {
+            *slot = value;
+            return Ok(());
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => Err(Self::undefined(name)),
+        }
+    }
+
+    /// Reads `name` from the scope exactly `distance` hops up the chain, as
+    /// computed by the resolver.
+    pub fn get_at(environment: &Rc<RefCell<Environment>>, distance: usize, name: &AstToken) -> Result<Value, RuntimeError> {
+        Self::value_at(environment, distance, name.lexeme()).ok_or_else(|| Self::undefined(name))
+    }
+
+    /// Like `get_at`, for implicit names such as `this` that have no token.
+    pub fn value_at(environment: &Rc<RefCell<Environment>>, distance: usize, name: &str) -> Option<Value> {
+        Self::ancestor(environment, distance)
+            .borrow()
+            .values
+            .get(name)
+            .cloned()
+    }
+
+    pub fn assign_at(environment: &Rc<RefCell<Environment>>, distance: usize, name: &AstToken, value: Value) -> Result<(), RuntimeError> {
+        let ancestor = Self::ancestor(environment, distance);
+        let mut ancestor = ancestor.borrow_mut();
+
+        match ancestor.values.get_mut(name.lexeme()) {
+            Some(slot) => {
+                *slot = value;
+                Ok(())
+            }
+            None => Err(Self::undefined(name)),
+        }
+    }
+
+    fn ancestor(environment: &Rc<RefCell<Environment>>, distance: usize) -> Rc<RefCell<Environment>> {
+        let mut environment = environment.clone();
+        for _ in 0..distance {
+            let enclosing = environment
+                .borrow()
+                .enclosing
+                .clone()
+                .expect("the resolver only counts scopes that exist at runtime");
+            environment = enclosing;
+        }
+
+        environment
+    }
+
+    fn undefined(name: &AstToken) -> RuntimeError {
+        RuntimeError::new(
+            &Code::UNDEFINED_VARIABLE,
+            name.line(),
+            name.span(),
+            format!("Undefined variable '{}'.", name.lexeme())
+        )
+    }
+}
@@ -0,0 +1,76 @@
+use crate::parse::FunctionDecl;
+use super::{Environment, LoxInstance, Value};
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+/// A user-defined function together with the environment it closes over.
+pub struct LoxFunction {
+    declaration: Rc<FunctionDecl>,
+    closure: Rc<RefCell<Environment>>,
+    // A class's `init` method, which always returns `this`.
+    is_initializer: bool,
+}
+
+impl LoxFunction {
+    pub fn new(declaration: Rc<FunctionDecl>, closure: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            declaration,
+            closure,
+            is_initializer: false,
+        }
+    }
+
+    pub fn new_method(declaration: Rc<FunctionDecl>, closure: Rc<RefCell<Environment>>) -> Self {
+        let is_initializer = declaration.name.lexeme() == "init";
+
+        Self {
+            declaration,
+            closure,
+            is_initializer,
+        }
+    }
+
+    /// The method with `this` bound to `instance`, in a scope of its own
+    /// between the method and its closure.
+    pub fn bind(&self, instance: Rc<LoxInstance>) -> LoxFunction {
+        let mut environment = Environment::with_enclosing(self.closure.clone());
+        environment.define("this", Value::Instance(instance));
+
+        Self {
+            declaration: self.declaration.clone(),
+            closure: Rc::new(RefCell::new(environment)),
+            is_initializer: self.is_initializer,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.declaration.name.lexeme()
+    }
+
+    pub fn arity(&self) -> usize {
+        self.declaration.params.len()
+    }
+
+    pub fn declaration(&self) -> &FunctionDecl {
+        &self.declaration
+    }
+
+    pub fn closure(&self) -> Rc<RefCell<Environment>> {
+        self.closure.clone()
+    }
+
+    pub fn is_initializer(&self) -> bool {
+        self.is_initializer
+    }
+}
+
+// The closure usually holds the function itself, so it is left out.
+impl fmt::Debug for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoxFunction")
+            .field("name", &self.name())
+            .finish_non_exhaustive()
+    }
+}
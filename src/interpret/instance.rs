@@ -0,0 +1,58 @@
+use crate::diagnostics::Code;
+use crate::parse::AstToken;
+use super::{LoxClass, RuntimeError, Value};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+/// An instance of a Lox class, holding its own fields.
+pub struct LoxInstance {
+    class: Rc<LoxClass>,
+    fields: RefCell<HashMap<String, Value>>,
+}
+
+impl LoxInstance {
+    pub fn new(class: Rc<LoxClass>) -> Self {
+        Self {
+            class,
+            fields: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn class(&self) -> &Rc<LoxClass> {
+        &self.class
+    }
+
+    /// Reads a field or, failing that, a method bound to `instance`. Fields
+    /// shadow methods.
+    pub fn get(instance: &Rc<LoxInstance>, name: &AstToken) -> Result<Value, RuntimeError> {
+        if let Some(value) = instance.fields.borrow().get(name.lexeme()) {
+            return Ok(value.clone());
+        }
+
+        match instance.class.find_method(name.lexeme()) {
+            Some(method) => Ok(Value::Function(Rc::new(method.bind(instance.clone())))),
+            None => Err(RuntimeError::new(
+                &Code::UNDEFINED_PROPERTY,
+                name.line(),
+                name.span(),
+                format!("Undefined property '{}'.", name.lexeme())
+            )),
+        }
+    }
+
+    pub fn set(&self, name: &AstToken, value: Value) {
+        self.fields.borrow_mut().insert(name.lexeme().to_string(), value);
+    }
+}
+
+// Fields often refer back to the instance, so they are left out.
+impl fmt::Debug for LoxInstance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoxInstance")
+            .field("class", &self.class.name())
+            .finish_non_exhaustive()
+    }
+}
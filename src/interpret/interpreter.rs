@@ -0,0 +1,457 @@
+use crate::diagnostics::Code;
+use crate::parse::{AstToken, Expr, ExprId, Literal, Stmt};
+use crate::resolve::Locals;
+use crate::tokenize::TokenType;
+use super::{standard_library, Environment, LoxClass, LoxFunction, LoxInstance, NativeFunction, RuntimeError, Value};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+type EvalResult = Result<Value, RuntimeError>;
+type ExecResult = Result<(), Unwind>;
+
+/// Deepest nesting of Lox calls before a "Stack overflow." runtime error.
+/// Each call uses several native frames, so the thread running the
+/// interpreter needs a stack well above the 8 MiB default to reach it.
+pub const MAX_CALL_DEPTH: usize = 10_000;
+
+/// Why execution of a statement stopped early.
+enum Unwind {
+    Error(RuntimeError),
+    // A `return` statement, carrying its value up to the enclosing call.
+    Return(Value),
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(runtime_error: RuntimeError) -> Self {
+        Unwind::Error(runtime_error)
+    }
+}
+
+/// Tree-walking interpreter over the parser's syntax tree.
+///
+/// Global state survives between calls to `interpret`, so a program can be
+/// fed one piece at a time. Local variables are looked up at the depth the
+/// resolver found for them, see `resolve`; anything unresolved is a global.
+pub struct Interpreter {
+    globals: Rc<RefCell<Environment>>,
+    environment: Rc<RefCell<Environment>>,
+    locals: Locals,
+    output: Box<dyn Write>,
+    call_depth: usize,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::with_output(Box::new(io::stdout()))
+    }
+
+    /// An interpreter whose `print` statements write to `output`.
+    pub fn with_output(output: Box<dyn Write>) -> Self {
+        let globals = Rc::new(RefCell::new(Environment::new()));
+
+        let mut interpreter = Self {
+            globals: globals.clone(),
+            environment: globals,
+            locals: Locals::new(),
+            output,
+            call_depth: 0,
+        };
+
+        for native in standard_library() {
+            interpreter.define_native(native);
+        }
+
+        interpreter
+    }
+
+    /// Forgets every global and resolved variable, leaving only the
+    /// standard library defined, as in a new interpreter. The output is
+    /// kept.
+    pub fn reset(&mut self) {
+        self.globals = Rc::new(RefCell::new(Environment::new()));
+        self.environment = self.globals.clone();
+        self.locals = Locals::new();
+        self.call_depth = 0;
+
+        for native in standard_library() {
+            self.define_native(native);
+        }
+    }
+
+    /// Makes `native` callable from Lox as a global under its name,
+    /// replacing any global already bound to it.
+    pub fn define_native(&mut self, native: NativeFunction) {
+        let name = native.name().to_string();
+        self.globals.borrow_mut().define(&name, Value::Native(Rc::new(native)));
+    }
+
+    /// The global variables and their values, sorted by name.
+    pub fn globals(&self) -> Vec<(String, Value)> {
+        let mut globals = self
+            .globals
+            .borrow()
+            .bindings()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect::<Vec<(String, Value)>>();
+        globals.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+        globals
+    }
+
+    /// Records the scope depths computed by the resolver. Called before
+    /// interpreting the statements they were computed for; earlier entries
+    /// are kept so previously defined functions still work.
+    pub fn resolve(&mut self, locals: Locals) {
+        self.locals.extend(locals);
+    }
+
+    pub fn interpret(&mut self, statements: &[Stmt]) -> Result<(), RuntimeError> {
+        for statement in statements {
+            match self.execute(statement) {
+                Ok(()) => {}
+                Err(Unwind::Error(runtime_error)) => return Err(runtime_error),
+                // A `return` outside any function ends the program.
+                Err(Unwind::Return(_)) => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, statement: &Stmt) -> ExecResult {
+        match statement {
+            Stmt::Expression(expr) => {
+                self.evaluate(expr)?;
+            }
+            Stmt::Print(expr) => {
+                let value = self.evaluate(expr)?;
+                // A closed stdout is not the program's fault, keep going.
+                let _ = writeln!(self.output, "{}", value);
+            }
+            Stmt::Var { name, initializer } => {
+                let value = match initializer {
+                    Some(initializer) => self.evaluate(initializer)?,
+                    None => Value::Nil,
+                };
+                self.environment.borrow_mut().define(name.lexeme(), value);
+            }
+            Stmt::Block(statements) => {
+                let environment = Environment::with_enclosing(self.environment.clone());
+                self.execute_block(statements, Rc::new(RefCell::new(environment)))?;
+            }
+            Stmt::If { condition, then_branch, else_branch } => {
+                if self.evaluate(condition)?.is_truthy() {
+                    self.execute(then_branch)?;
+                } else if let Some(else_branch) = else_branch {
+                    self.execute(else_branch)?;
+                }
+            }
+            Stmt::While { condition, body } => {
+                while self.evaluate(condition)?.is_truthy() {
+                    self.execute(body)?;
+                }
+            }
+            Stmt::Function(declaration) => {
+                let function = LoxFunction::new(declaration.clone(), self.environment.clone());
+                self.environment.borrow_mut().define(
+                    declaration.name.lexeme(),
+                    Value::Function(Rc::new(function))
+                );
+            }
+            Stmt::Class { name, superclass, methods } => {
+                let superclass = match superclass {
+                    Some(superclass) => match self.evaluate(superclass)? {
+                        Value::Class(class) => Some(class),
+                        _ => {
+                            let Expr::Variable { name, .. } = superclass else {
+                                unreachable!("the parser only builds superclass variables");
+                            };
+                            return Err(Self::error(&Code::SUPERCLASS_NOT_CLASS, name, "Superclass must be a class.").into());
+                        }
+                    },
+                    None => None,
+                };
+
+                self.environment.borrow_mut().define(name.lexeme(), Value::Nil);
+
+                // Methods of a subclass close over a scope holding `super`.
+                let closure = match &superclass {
+                    Some(superclass) => {
+                        let mut environment = Environment::with_enclosing(self.environment.clone());
+                        environment.define("super", Value::Class(superclass.clone()));
+                        Rc::new(RefCell::new(environment))
+                    }
+                    None => self.environment.clone(),
+                };
+
+                let methods = methods
+                    .iter()
+                    .map(|method| {
+                        let function = LoxFunction::new_method(method.clone(), closure.clone());
+                        (method.name.lexeme().to_string(), Rc::new(function))
+                    })
+                    .collect::<HashMap<String, Rc<LoxFunction>>>();
+
+                let class = LoxClass::new(name.lexeme().to_string(), superclass, methods);
+                self.environment.borrow_mut().assign(name, Value::Class(Rc::new(class)))?;
+            }
+            Stmt::Return { value, .. } => {
+                let value = match value {
+                    Some(value) => self.evaluate(value)?,
+                    None => Value::Nil,
+                };
+                return Err(Unwind::Return(value));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs `statements` in `environment`, restoring the current scope
+    /// afterwards even if one of them fails.
+    fn execute_block(&mut self, statements: &[Stmt], environment: Rc<RefCell<Environment>>) -> ExecResult {
+        let previous = std::mem::replace(&mut self.environment, environment);
+        let result = statements
+            .iter()
+            .try_for_each(|statement| self.execute(statement));
+        self.environment = previous;
+
+        result
+    }
+
+    pub fn evaluate(&mut self, expr: &Expr) -> EvalResult {
+        match expr {
+            Expr::Literal(literal) => Ok(Self::literal(literal)),
+            Expr::Grouping(expression) => self.evaluate(expression),
+            Expr::Unary { operator, right } => {
+                let right = self.evaluate(right)?;
+                Self::unary(operator, right)
+            }
+            Expr::Binary { left, operator, right } => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+                Self::binary(left, operator, right)
+            }
+            Expr::Logical { left, operator, right } => {
+                let left = self.evaluate(left)?;
+
+                // Short-circuit, yielding the operand that decided the result.
+                let decided = match operator.token_type() {
+                    TokenType::Or => left.is_truthy(),
+                    _ => !left.is_truthy(),
+                };
+                if decided {
+                    return Ok(left);
+                }
+
+                self.evaluate(right)
+            }
+            Expr::Variable { id, name } => self.look_up_variable(*id, name),
+            Expr::Assign { id, name, value } => {
+                let value = self.evaluate(value)?;
+
+                match self.locals.get(id) {
+                    Some(&distance) => Environment::assign_at(&self.environment, distance, name, value.clone())?,
+                    None => self.globals.borrow_mut().assign(name, value.clone())?,
+                }
+
+                Ok(value)
+            }
+            Expr::Call { callee, paren, arguments } => {
+                let callee = self.evaluate(callee)?;
+
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| self.evaluate(argument))
+                    .collect::<Result<Vec<Value>, RuntimeError>>()?;
+
+                self.call(callee, paren, arguments)
+            }
+            Expr::Get { object, name } => match self.evaluate(object)? {
+                Value::Instance(instance) => LoxInstance::get(&instance, name),
+                _ => Err(Self::error(&Code::NOT_AN_INSTANCE, name, "Only instances have properties.")),
+            },
+            Expr::Set { object, name, value } => {
+                let Value::Instance(instance) = self.evaluate(object)? else {
+                    return Err(Self::error(&Code::NOT_AN_INSTANCE, name, "Only instances have fields."));
+                };
+
+                let value = self.evaluate(value)?;
+                instance.set(name, value.clone());
+
+                Ok(value)
+            }
+            Expr::This { id, keyword } => self.look_up_variable(*id, keyword),
+            Expr::Super { id, keyword, method } => self.super_method(*id, keyword, method),
+        }
+    }
+
+    /// Finds `method` on the superclass and binds it to the current `this`,
+    /// which lives in the scope just inside the one holding `super`.
+    fn super_method(&self, id: ExprId, keyword: &AstToken, method: &AstToken) -> EvalResult {
+        // Expressions run without the resolver, like those of `evaluate`,
+        // look `super` up as a global, which it never is, as for `this`.
+        let Some(&distance) = self.locals.get(&id) else {
+            return self.globals.borrow().get(keyword);
+        };
+
+        let Ok(Value::Class(superclass)) = Environment::get_at(&self.environment, distance, keyword) else {
+            unreachable!("the resolver only binds `super` inside subclasses");
+        };
+        let Some(Value::Instance(instance)) = Environment::value_at(&self.environment, distance - 1, "this") else {
+            unreachable!("the resolver only binds `super` inside methods");
+        };
+
+        match superclass.find_method(method.lexeme()) {
+            Some(function) => Ok(Value::Function(Rc::new(function.bind(instance)))),
+            None => Err(Self::error(
+                &Code::UNDEFINED_PROPERTY,
+                method,
+                &format!("Undefined property '{}'.", method.lexeme())
+            )),
+        }
+    }
+
+    fn look_up_variable(&self, id: ExprId, name: &AstToken) -> EvalResult {
+        match self.locals.get(&id) {
+            Some(&distance) => Environment::get_at(&self.environment, distance, name),
+            None => self.globals.borrow().get(name),
+        }
+    }
+
+    fn call(&mut self, callee: Value, paren: &AstToken, arguments: Vec<Value>) -> EvalResult {
+        let arity = match &callee {
+            Value::Function(function) => function.arity(),
+            Value::Native(native) => native.arity(),
+            Value::Class(class) => class.arity(),
+            _ => return Err(Self::error(&Code::NOT_CALLABLE, paren, "Can only call functions and classes.")),
+        };
+
+        if arguments.len() != arity {
+            let message = format!(
+                "Expected {} arguments but got {}.",
+                arity,
+                arguments.len()
+            );
+            return Err(Self::error(&Code::ARITY_MISMATCH, paren, &message));
+        }
+
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(Self::error(&Code::STACK_OVERFLOW, paren, "Stack overflow."));
+        }
+
+        self.call_depth += 1;
+        let result = match callee {
+            Value::Class(class) => self.instantiate(class, arguments),
+            Value::Function(function) => self.call_function(&function, arguments),
+            Value::Native(native) => native
+                .call(&arguments)
+                .map_err(|message| Self::error(&Code::NATIVE_ERROR, paren, &message)),
+            _ => unreachable!("checked above"),
+        };
+        self.call_depth -= 1;
+
+        result
+    }
+
+    /// Creates an instance of `class` and runs its `init` method on it.
+    fn instantiate(&mut self, class: Rc<LoxClass>, arguments: Vec<Value>) -> EvalResult {
+        let instance = Rc::new(LoxInstance::new(class.clone()));
+
+        if let Some(initializer) = class.find_method("init") {
+            self.call_function(&initializer.bind(instance.clone()), arguments)?;
+        }
+
+        Ok(Value::Instance(instance))
+    }
+
+    fn call_function(&mut self, function: &LoxFunction, arguments: Vec<Value>) -> EvalResult {
+        let mut environment = Environment::with_enclosing(function.closure());
+        for (param, argument) in function.declaration().params.iter().zip(arguments) {
+            environment.define(param.lexeme(), argument);
+        }
+
+        let body = &function.declaration().body;
+        let value = match self.execute_block(body, Rc::new(RefCell::new(environment))) {
+            Ok(()) => Value::Nil,
+            Err(Unwind::Return(value)) => value,
+            Err(Unwind::Error(runtime_error)) => return Err(runtime_error),
+        };
+
+        // An initializer returns `this`, even when called directly or when
+        // it exits through a bare `return;`.
+        if function.is_initializer() {
+            let this = Environment::value_at(&function.closure(), 0, "this");
+            return Ok(this.expect("initializers are always bound"));
+        }
+
+        Ok(value)
+    }
+
+    fn literal(literal: &Literal) -> Value {
+        match literal {
+            Literal::Nil => Value::Nil,
+            Literal::Bool(value) => Value::Bool(*value),
+            Literal::Number(value) => Value::Number(*value),
+            Literal::String(value) => Value::String(value.as_str().into()),
+        }
+    }
+
+    fn unary(operator: &AstToken, right: Value) -> EvalResult {
+        match operator.token_type() {
+            TokenType::Bang => Ok(Value::Bool(!right.is_truthy())),
+            TokenType::Minus => match right {
+                Value::Number(value) => Ok(Value::Number(-value)),
+                _ => Err(Self::error(&Code::OPERAND_NOT_NUMBER, operator, "Operand must be a number.")),
+            },
+            _ => unreachable!("the parser only builds unary `!` and `-`"),
+        }
+    }
+
+    fn binary(left: Value, operator: &AstToken, right: Value) -> EvalResult {
+        match operator.token_type() {
+            TokenType::EqualEqual => return Ok(Value::Bool(left == right)),
+            TokenType::BangEqual => return Ok(Value::Bool(left != right)),
+            TokenType::Plus => {
+                return match (left, right) {
+                    (Value::Number(left), Value::Number(right)) => Ok(Value::Number(left + right)),
+                    (Value::String(left), Value::String(right)) => {
+                        Ok(Value::String(format!("{}{}", left, right).into()))
+                    }
+                    _ => Err(Self::error(&Code::INVALID_ADDITION, operator, "Operands must be two numbers or two strings.")),
+                };
+            }
+            _ => {}
+        }
+
+        let (Value::Number(left), Value::Number(right)) = (left, right) else {
+            return Err(Self::error(&Code::OPERANDS_NOT_NUMBERS, operator, "Operands must be numbers."));
+        };
+
+        let value = match operator.token_type() {
+            TokenType::Minus => Value::Number(left - right),
+            TokenType::Star => Value::Number(left * right),
+            TokenType::Slash => Value::Number(left / right),
+            TokenType::Greater => Value::Bool(left > right),
+            TokenType::GreaterEqual => Value::Bool(left >= right),
+            TokenType::Less => Value::Bool(left < right),
+            TokenType::LessEqual => Value::Bool(left <= right),
+            _ => unreachable!("the parser only builds arithmetic, comparison and equality operators"),
+        };
+
+        Ok(value)
+    }
+
+    fn error(code: &'static Code, token: &AstToken, message: &str) -> RuntimeError {
+        RuntimeError::new(code, token.line(), token.span(), message.to_string())
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,185 @@
+mod value;
+pub use value::Value;
+
+mod runtime_error;
+pub use runtime_error::RuntimeError;
+
+mod environment;
+pub use environment::Environment;
+
+mod function;
+pub use function::LoxFunction;
+
+mod native;
+pub use native::{standard_library, NativeFn, NativeFunction};
+
+mod class;
+pub use class::LoxClass;
+
+mod instance;
+pub use instance::LoxInstance;
+
+mod interpreter;
+pub use interpreter::{Interpreter, MAX_CALL_DEPTH};
+
+#[cfg(test)]
+mod shared_output;
+#[cfg(test)]
+pub(crate) use shared_output::SharedOutput;
+
+#[cfg(test)]
+use crate::parse::{parse, parse_expression};
+#[cfg(test)]
+use crate::resolve::resolve;
+#[cfg(test)]
+use crate::tokenize::tokenize;
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+/// Runs `file_contents`, returning what it printed followed by the runtime
+/// error, if any.
+#[cfg(test)]
+fn run_to_string(file_contents: &str) -> String {
+    let (tokens, _) = tokenize(file_contents);
+    let statements = parse(tokens).unwrap();
+    let locals = resolve(&statements).unwrap();
+
+    let output = SharedOutput::new();
+    let mut interpreter = Interpreter::with_output(Box::new(output.clone()));
+    interpreter.resolve(locals);
+    let result = interpreter.interpret(&statements);
+
+    let mut printed = output.contents();
+    if let Err(runtime_error) = result {
+        printed.push_str(&runtime_error.to_string());
+    }
+
+    printed
+}
+
+#[cfg(test)]
+fn evaluate_to_string(file_contents: &str) -> String {
+    let (tokens, _) = tokenize(file_contents);
+    let expr = parse_expression(tokens).unwrap();
+
+    match Interpreter::new().evaluate(&expr) {
+        Ok(value) => value.to_string(),
+        Err(runtime_error) => runtime_error.to_string(),
+    }
+}
+
+#[test]
+fn test_evaluate_literals() {
+    assert_eq!(evaluate_to_string("nil"), "nil");
+    assert_eq!(evaluate_to_string("true"), "true");
+    assert_eq!(evaluate_to_string("10.40"), "10.4");
+    assert_eq!(evaluate_to_string("\"hello world!\""), "hello world!");
+    assert_eq!(evaluate_to_string("((false))"), "false");
+}
+
+#[test]
+fn test_evaluate_unary() {
+    assert_eq!(evaluate_to_string("-73"), "-73");
+    assert_eq!(evaluate_to_string("!true"), "false");
+    assert_eq!(evaluate_to_string("!nil"), "true");
+    assert_eq!(evaluate_to_string("!!10.40"), "true");
+}
+
+#[test]
+fn test_evaluate_arithmetic() {
+    assert_eq!(evaluate_to_string("42 / 5"), "8.4");
+    assert_eq!(evaluate_to_string("18 * 3 / (3 * 6)"), "3");
+    assert_eq!(evaluate_to_string("(10.40 * 2) / 2"), "10.4");
+    assert_eq!(evaluate_to_string("70 - 65 + 2 * 3"), "11");
+    assert_eq!(evaluate_to_string("\"hello\" + \" world\""), "hello world");
+}
+
+#[test]
+fn test_evaluate_comparison_and_equality() {
+    assert_eq!(evaluate_to_string("57 > -65"), "true");
+    assert_eq!(evaluate_to_string("11 >= 11"), "true");
+    assert_eq!(evaluate_to_string("(54 - 67) >= -(114 / 57 + 11)"), "true");
+    assert_eq!(evaluate_to_string("\"hello\" == \"world\""), "false");
+    assert_eq!(evaluate_to_string("\"foo\" != \"bar\""), "true");
+    assert_eq!(evaluate_to_string("61 == \"61\""), "false");
+    assert_eq!(evaluate_to_string("nil == nil"), "true");
+}
+
+#[test]
+fn test_evaluate_runtime_errors() {
+    assert_eq!(evaluate_to_string("-\"muffin\""), "Operand must be a number.\n[line 1]");
+    assert_eq!(evaluate_to_string("\"a\" * 2"), "Operands must be numbers.\n[line 1]");
+    assert_eq!(evaluate_to_string("true\n< 2"), "Operands must be numbers.\n[line 2]");
+    assert_eq!(
+        evaluate_to_string("\"foo\" + true"),
+        "Operands must be two numbers or two strings.\n[line 1]"
+    );
+    assert_eq!(evaluate_to_string("this"), "Undefined variable 'this'.\n[line 1]");
+    assert_eq!(evaluate_to_string("super.x"), "Undefined variable 'super'.\n[line 1]");
+}
+
+#[test]
+fn test_run_statements() {
+    assert_eq!(run_to_string("print 1; print \"two\";"), "1\ntwo\n");
+    assert_eq!(run_to_string("var a = 1; { var b = a + 1; var a = b; print a; } print a;"), "2\n1\n");
+    assert_eq!(
+        run_to_string("for (var i = 0; i < 3; i = i + 1) if (i != 1) print i;"),
+        "0\n2\n"
+    );
+}
+
+#[test]
+fn test_run_runtime_error_keeps_prior_output() {
+    assert_eq!(
+        run_to_string("print \"ok\";\nprint x;\nprint \"unreachable\";"),
+        "ok\nUndefined variable 'x'.\n[line 2]"
+    );
+}
+
+#[test]
+fn test_runtime_error_codes() {
+    let code = |source| {
+        let (tokens, _) = tokenize(source);
+        let statements = parse(tokens).unwrap();
+        let mut interpreter = Interpreter::with_output(Box::new(SharedOutput::new()));
+        interpreter.resolve(resolve(&statements).unwrap());
+
+        interpreter.interpret(&statements).unwrap_err().code().id()
+    };
+
+    assert_eq!(code("-\"a\";"), "L0301");
+    assert_eq!(code("print x;"), "L0304");
+    assert_eq!(code("class A {} A().x;"), "L0305");
+    assert_eq!(code("fun f(a) {} f();"), "L0307");
+    assert_eq!(code("1.size = 2;"), "L0309");
+}
+
+#[test]
+fn test_interpret_keeps_globals_between_calls() {
+    let output = SharedOutput::new();
+    let mut interpreter = Interpreter::with_output(Box::new(output.clone()));
+
+    for line in ["var a = 20;", "a = a + 1;", "print a;"] {
+        let (tokens, _) = tokenize(line);
+        interpreter.interpret(&parse(tokens).unwrap()).unwrap();
+    }
+
+    assert_eq!(output.contents(), "21\n");
+}
+
+#[test]
+fn test_run_functions_and_closures() {
+    assert_eq!(
+        run_to_string("fun fib(n) { if (n < 2) return n; return fib(n - 1) + fib(n - 2); } print fib(10);"),
+        "55\n"
+    );
+    assert_eq!(
+        run_to_string("fun outer() { var x = \"captured\"; fun inner() { print x; } return inner; } outer()();"),
+        "captured\n"
+    );
+    assert_eq!(
+        run_to_string("fun f(a) {}\nprint f;\nf();"),
+        "<fn f>\nExpected 1 arguments but got 0.\n[line 3]"
+    );
+}
@@ -0,0 +1,86 @@
+use super::Value;
+
+use std::fmt;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The host side of a native function. It gets the already evaluated
+/// arguments, exactly `arity` of them, and returns the result or the
+/// message of a runtime error.
+pub type NativeFn = dyn Fn(&[Value]) -> Result<Value, String>;
+
+/// A function implemented in Rust and callable from Lox.
+pub struct NativeFunction {
+    name: String,
+    arity: usize,
+    function: Rc<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, arity: usize, function: F) -> Self
+    where
+        F: Fn(&[Value]) -> Result<Value, String> + 'static,
+    {
+        Self {
+            name: name.to_string(),
+            arity,
+            function: Rc::new(function),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    pub fn call(&self, arguments: &[Value]) -> Result<Value, String> {
+        (self.function)(arguments)
+    }
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NativeFunction")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The natives every interpreter starts with.
+pub fn standard_library() -> Vec<NativeFunction> {
+    vec![
+        NativeFunction::new("clock", 0, |_| Ok(Value::Number(clock()))),
+    ]
+}
+
+/// Seconds since the Unix epoch, with sub-second precision.
+fn clock() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64())
+}
+
+#[test]
+fn test_native_function_call() {
+    let add = NativeFunction::new("add", 2, |arguments| match arguments {
+        [Value::Number(left), Value::Number(right)] => Ok(Value::Number(left + right)),
+        _ => Err("Operands must be numbers.".to_string()),
+    });
+
+    assert_eq!(add.name(), "add");
+    assert_eq!(add.arity(), 2);
+    assert_eq!(add.call(&[Value::Number(1.0), Value::Number(2.0)]), Ok(Value::Number(3.0)));
+    assert_eq!(add.call(&[Value::Nil, Value::Nil]), Err("Operands must be numbers.".to_string()));
+}
+
+#[test]
+fn test_clock_is_after_the_epoch() {
+    let clock = &standard_library()[0];
+
+    assert_eq!(clock.name(), "clock");
+    assert!(matches!(clock.call(&[]), Ok(Value::Number(seconds)) if seconds > 0.0));
+}
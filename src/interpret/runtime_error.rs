@@ -0,0 +1,62 @@
+use crate::diagnostics::Code;
+use crate::tokenize::Span;
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    code: &'static Code,
+    line: u32,
+    span: Option<Span>,
+    message: String,
+}
+
+impl RuntimeError {
+    pub fn new(code: &'static Code, line: u32, span: Option<Span>, message: String) -> Self {
+        Self {
+            code,
+            line,
+            span,
+            message,
+        }
+    }
+
+    pub fn code(&self) -> &'static Code {
+        self.code
+    }
+
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\n[line {}]",
+            self.message,
+            self.line
+        )
+    }
+}
+
+#[test]
+fn test_runtime_error_display() {
+    let runtime_error = RuntimeError::new(
+        &Code::OPERAND_NOT_NUMBER,
+        4,
+        None,
+        "Operand must be a number.".to_string(),
+    );
+
+    assert_eq!(runtime_error.to_string(), "Operand must be a number.\n[line 4]");
+}
@@ -0,0 +1,82 @@
+use super::{LoxClass, LoxFunction, LoxInstance, NativeFunction};
+
+use std::fmt;
+use std::rc::Rc;
+
+/// A runtime Lox value.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    String(Rc<str>),
+    Function(Rc<LoxFunction>),
+    Native(Rc<NativeFunction>),
+    Class(Rc<LoxClass>),
+    Instance(Rc<LoxInstance>),
+}
+
+impl Value {
+    /// `nil` and `false` are falsey, everything else is truthy.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Bool(false))
+    }
+}
+
+// Lox equality: values of different types are never equal, and there is no
+// implicit conversion between them.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Bool(left), Value::Bool(right)) => left == right,
+            (Value::Number(left), Value::Number(right)) => left == right,
+            (Value::String(left), Value::String(right)) => left == right,
+            // Functions, classes and instances are only equal to themselves.
+            (Value::Function(left), Value::Function(right)) => Rc::ptr_eq(left, right),
+            (Value::Native(left), Value::Native(right)) => Rc::ptr_eq(left, right),
+            (Value::Class(left), Value::Class(right)) => Rc::ptr_eq(left, right),
+            (Value::Instance(left), Value::Instance(right)) => Rc::ptr_eq(left, right),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Bool(value) => write!(f, "{}", value),
+            // Integral numbers print without a fractional part: `10`, `3.5`.
+            Value::Number(value) => write!(f, "{}", value),
+            Value::String(value) => write!(f, "{}", value),
+            Value::Function(function) => write!(f, "<fn {}>", function.name()),
+            Value::Native(_) => write!(f, "<native fn>"),
+            Value::Class(class) => write!(f, "{}", class.name()),
+            Value::Instance(instance) => write!(f, "{} instance", instance.class().name()),
+        }
+    }
+}
+
+#[test]
+fn test_value_display() {
+    assert_eq!(Value::Nil.to_string(), "nil");
+    assert_eq!(Value::Bool(true).to_string(), "true");
+    assert_eq!(Value::Number(10.0).to_string(), "10");
+    assert_eq!(Value::Number(3.5).to_string(), "3.5");
+    assert_eq!(Value::Number(-0.25).to_string(), "-0.25");
+    assert_eq!(Value::String("hi".into()).to_string(), "hi");
+}
+
+#[test]
+fn test_value_truthiness_and_equality() {
+    assert!(!Value::Nil.is_truthy());
+    assert!(!Value::Bool(false).is_truthy());
+    assert!(Value::Number(0.0).is_truthy());
+    assert!(Value::String("".into()).is_truthy());
+
+    assert_eq!(Value::Nil, Value::Nil);
+    assert_eq!(Value::String("a".into()), Value::String("a".into()));
+    assert_ne!(Value::Number(1.0), Value::String("1".into()));
+    assert_ne!(Value::Nil, Value::Bool(false));
+}
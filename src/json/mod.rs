@@ -0,0 +1,200 @@
+//! Machine-readable output, for `--format=json`.
+//!
+//! With it every command prints JSON Lines on stdout: one object per line,
+//! each with a `kind` telling what it is. The members listed here are
+//! always present, in this order, and keep their meaning; later versions
+//! may add kinds and members but won't change these.
+//!
+//! - `token`, from `tokenize`, one per token including the final `EOF`:
+//!   `{"kind":"token","type":"NUMBER","lexeme":"1.50","literal":1.5,"line":1,"column":7,"span":SPAN}`.
+//!   `literal` is the value of a string or number literal, `null` for
+//!   other tokens.
+//! - `error`, from any command, one per error, and `warning` and `note`,
+//!   from `run`, one per warning or note not allowed with `--allow`. All
+//!   are written from the same `Diagnostic` pretty text output renders:
+//!   `{"kind":"error","phase":"parser","message":"Expect expression.","line":1,"column":9,"span":SPAN,"code":"L0101","severity":"error","label":"found `;`","secondary":[],"notes":[],"help":null}`.
+//!   `kind` and `severity` are both the severity, `error`, `warning` or
+//!   `note`, and `phase` one of `lexer`, `parser`, `resolver` or `runtime`.
+//!   `column` and `span` are `null` for diagnostics whose position within
+//!   the line is unknown. `code` is the id `explain`
+//!   describes. `label` is the text shown under the span, `secondary`
+//!   lists related places as `{"span":SPAN,"text":"first declared here"}`,
+//!   `notes` gives context and `help` suggests a fix; each is `null` or
+//!   empty when there is none.
+//! - `expression`, from `parse`: `{"kind":"expression","text":"(+ 1.0 2.0)"}`.
+//! - `value`, from `evaluate`, and from the REPL for each bare expression:
+//!   `{"kind":"value","text":"3"}`.
+//! - `output`, from `run`, one per line the program prints, and from the
+//!   REPL, one per line printed or output by a meta-command:
+//!   `{"kind":"output","text":"hello"}`.
+//! - `disassembly`, from `disassemble`, with the whole listing:
+//!   `{"kind":"disassembly","text":"== <script> ==\n..."}`.
+//! - `code`, from `explain`, one per diagnostic code, or only the one
+//!   asked for:
+//!   `{"kind":"code","id":"L0001","name":"unexpected-character","phase":"lexer","explanation":"A character...","severity":"error"}`.
+//!
+//! `SPAN` is `{"start":POSITION,"end":POSITION}`, the end being exclusive,
+//! and `POSITION` is `{"line":1,"column":1,"offset":0}`: lines and columns
+//! count chars from 1, offsets count bytes from 0.
+//!
+//! Records come in the order they happen; tokens and lexer errors in
+//! source order. Exit codes are the same as with text output.
+//!
+//! Problems with the command line itself, like an unknown option value
+//! or diagnostic code, are still reported as text on stderr, with exit
+//! code 64. REPL meta-commands that can't run, like an unknown one, are
+//! reported as text on stderr too, and the session goes on.
+
+mod value;
+pub use value::Json;
+
+mod output_lines;
+pub use output_lines::OutputLines;
+
+use crate::diagnostics::{Code, Diagnostic};
+use crate::tokenize::{LiteralType, Position, Span, Token};
+
+#[cfg(test)]
+use crate::bytecode::CompileError;
+#[cfg(test)]
+use crate::parse::ParseError;
+#[cfg(test)]
+use crate::tokenize::{Lexer, TokenType, TokenizerError};
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+impl From<&Token<'_>> for Json {
+    fn from(token: &Token<'_>) -> Self {
+        let literal = match token.literal() {
+            Some(LiteralType::String(string)) => Json::String(string.to_string()),
+            Some(LiteralType::Number(number)) => Json::Number(*number),
+            None => Json::Null,
+        };
+
+        Json::Object(vec![
+            ("kind", Json::String("token".to_string())),
+            ("type", Json::String(token.token_type().to_string())),
+            ("lexeme", Json::String(token.lexeme().to_string())),
+            ("literal", literal),
+            ("line", Json::Number(token.line() as f64)),
+            ("column", token.column().map_or(Json::Null, |column| Json::Number(column as f64))),
+            ("span", token.span().map_or(Json::Null, span)),
+        ])
+    }
+}
+
+impl From<&Code> for Json {
+    fn from(code: &Code) -> Self {
+        Json::Object(vec![
+            ("kind", Json::String("code".to_string())),
+            ("id", Json::String(code.id().to_string())),
+            ("name", Json::String(code.name().to_string())),
+            ("phase", Json::String(code.phase().to_string())),
+            ("explanation", Json::String(code.explanation().to_string())),
+            ("severity", Json::String(code.severity().to_string())),
+        ])
+    }
+}
+
+impl From<&Diagnostic> for Json {
+    fn from(diagnostic: &Diagnostic) -> Self {
+        let (column, diagnostic_span) = match diagnostic.span() {
+            Some(diagnostic_span) => (Json::Number(diagnostic_span.start().column() as f64), span(diagnostic_span)),
+            None => (Json::Null, Json::Null),
+        };
+        let secondary = diagnostic
+            .secondary()
+            .iter()
+            .map(|label| Json::Object(vec![("span", span(label.span())), ("text", Json::String(label.text().to_string()))]))
+            .collect();
+        let notes = diagnostic
+            .notes()
+            .iter()
+            .map(|note| Json::String(note.clone()))
+            .collect();
+
+        Json::Object(vec![
+            ("kind", Json::String(diagnostic.severity().to_string())),
+            ("phase", Json::String(diagnostic.code().phase().to_string())),
+            ("message", Json::String(diagnostic.message().to_string())),
+            ("line", Json::Number(diagnostic.line() as f64)),
+            ("column", column),
+            ("span", diagnostic_span),
+            ("code", Json::String(diagnostic.code().id().to_string())),
+            ("severity", Json::String(diagnostic.severity().to_string())),
+            ("label", diagnostic.label().map_or(Json::Null, |label| Json::String(label.to_string()))),
+            ("secondary", Json::Array(secondary)),
+            ("notes", Json::Array(notes)),
+            ("help", diagnostic.help().map_or(Json::Null, |help| Json::String(help.to_string()))),
+        ])
+    }
+}
+
+fn span(span: Span) -> Json {
+    Json::Object(vec![("start", position(span.start())), ("end", position(span.end()))])
+}
+
+fn position(position: Position) -> Json {
+    Json::Object(vec![
+        ("line", Json::Number(position.line() as f64)),
+        ("column", Json::Number(position.column() as f64)),
+        ("offset", Json::Number(position.offset() as f64)),
+    ])
+}
+
+#[test]
+fn test_token_and_lexer_error_schema() {
+    let records = Lexer::new("x = \"é\" $ 1.50")
+        .filter_map(|result| match result {
+            Ok(token) if token.token_type() == &TokenType::Error => None,
+            Ok(token) => Some(Json::from(&token).to_string()),
+            Err(token_error) => Some(Json::from(&Diagnostic::from(&token_error)).to_string()),
+        })
+        .collect::<Vec<String>>();
+
+    assert_eq!(
+        records,
+        [
+            r#"{"kind":"token","type":"IDENTIFIER","lexeme":"x","literal":null,"line":1,"column":1,"span":{"start":{"line":1,"column":1,"offset":0},"end":{"line":1,"column":2,"offset":1}}}"#,
+            r#"{"kind":"token","type":"EQUAL","lexeme":"=","literal":null,"line":1,"column":3,"span":{"start":{"line":1,"column":3,"offset":2},"end":{"line":1,"column":4,"offset":3}}}"#,
+            r#"{"kind":"token","type":"STRING","lexeme":"\"é\"","literal":"é","line":1,"column":5,"span":{"start":{"line":1,"column":5,"offset":4},"end":{"line":1,"column":8,"offset":8}}}"#,
+            r#"{"kind":"error","phase":"lexer","message":"Unexpected character: $","line":1,"column":9,"span":{"start":{"line":1,"column":9,"offset":9},"end":{"line":1,"column":10,"offset":10}},"code":"L0001","severity":"error","label":null,"secondary":[],"notes":[],"help":null}"#,
+            r#"{"kind":"token","type":"NUMBER","lexeme":"1.50","literal":1.5,"line":1,"column":11,"span":{"start":{"line":1,"column":11,"offset":11},"end":{"line":1,"column":15,"offset":15}}}"#,
+            r#"{"kind":"token","type":"EOF","lexeme":"","literal":null,"line":1,"column":15,"span":{"start":{"line":1,"column":15,"offset":15},"end":{"line":1,"column":15,"offset":15}}}"#,
+        ]
+    );
+}
+
+#[test]
+fn test_error_schema() {
+    let parse_error = ParseError::new(
+        &Code::EXPECTED_EXPRESSION,
+        2,
+        Some(Span::new(Position::new(2, 3, 10), Position::new(2, 4, 11))),
+        Some(")".to_string()),
+        "Expect expression.".to_string(),
+    );
+    assert_eq!(
+        Json::from(&Diagnostic::from(&CompileError::Parse(parse_error))).to_string(),
+        r#"{"kind":"error","phase":"parser","message":"Expect expression.","line":2,"column":3,"span":{"start":{"line":2,"column":3,"offset":10},"end":{"line":2,"column":4,"offset":11}},"code":"L0101","severity":"error","label":"found `)`","secondary":[],"notes":[],"help":null}"#
+    );
+
+    // Errors built without a source only know their line.
+    let token_error = TokenizerError::new(&Code::UNTERMINATED_STRING, 4, "Unterminated string.".to_string());
+    assert_eq!(
+        Json::from(&Diagnostic::from(&token_error)).to_string(),
+        r#"{"kind":"error","phase":"lexer","message":"Unterminated string.","line":4,"column":null,"span":null,"code":"L0002","severity":"error","label":null,"secondary":[],"notes":[],"help":"close the string with a `\"`"}"#
+    );
+
+    // Secondary labels and notes are arrays, empty when there are none.
+    let diagnostic = Diagnostic::new(&Code::UNUSED_VARIABLE, 3, "Local variable 'a' is never used.".to_string())
+        .with_span(Span::new(Position::new(3, 5, 26), Position::new(3, 6, 27)))
+        .with_label("never used".to_string())
+        .with_secondary(Span::new(Position::new(1, 5, 4), Position::new(1, 6, 5)), "shadows this one".to_string())
+        .with_note("one note".to_string());
+    assert_eq!(
+        Json::from(&diagnostic).to_string(),
+        r#"{"kind":"warning","phase":"resolver","message":"Local variable 'a' is never used.","line":3,"column":5,"span":{"start":{"line":3,"column":5,"offset":26},"end":{"line":3,"column":6,"offset":27}},"code":"L0209","severity":"warning","label":"never used","secondary":[{"span":{"start":{"line":1,"column":5,"offset":4},"end":{"line":1,"column":6,"offset":5}},"text":"shadows this one"}],"notes":["one note"],"help":null}"#
+    );
+}
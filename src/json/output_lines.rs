@@ -0,0 +1,74 @@
+use super::Json;
+
+use std::io::{self, Write};
+
+/// Wraps another writer so that each line written to it comes out as a
+/// `{"kind":"output","text":...}` record. Used as the `print` destination
+/// of programs run with `--format=json`.
+pub struct OutputLines<W: Write> {
+    inner: W,
+    // Text written since the last newline.
+    line: Vec<u8>,
+}
+
+impl<W: Write> OutputLines<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            line: Vec::new(),
+        }
+    }
+
+    fn write_record(&mut self) -> io::Result<()> {
+        let text = String::from_utf8_lossy(&self.line).into_owned();
+        self.line.clear();
+
+        writeln!(self.inner, "{}", Json::text("output", &text))
+    }
+}
+
+impl<W: Write> Write for OutputLines<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            match byte {
+                b'\n' => self.write_record()?,
+                byte => self.line.push(byte),
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    /// Flushes the inner writer. A line still missing its newline is kept
+    /// until it gets one, or until the wrapper is dropped.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for OutputLines<W> {
+    fn drop(&mut self) {
+        if !self.line.is_empty() {
+            let _ = self.write_record();
+        }
+        let _ = self.inner.flush();
+    }
+}
+
+#[test]
+fn test_output_lines() {
+    let mut buffer = Vec::new();
+    {
+        let mut output = OutputLines::new(&mut buffer);
+        write!(output, "hello\nwor").unwrap();
+        write!(output, "ld \"quoted\"\n\npartial").unwrap();
+    }
+
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "{\"kind\":\"output\",\"text\":\"hello\"}\n\
+         {\"kind\":\"output\",\"text\":\"world \\\"quoted\\\"\"}\n\
+         {\"kind\":\"output\",\"text\":\"\"}\n\
+         {\"kind\":\"output\",\"text\":\"partial\"}\n"
+    );
+}
@@ -0,0 +1,89 @@
+use std::fmt;
+
+/// A JSON value, written compactly by `Display`. Object members keep the
+/// order they were given in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    /// `{"kind": kind, "text": text}`, the shape of every record carrying
+    /// plain text.
+    pub fn text(kind: &str, text: &str) -> Self {
+        Json::Object(vec![
+            ("kind", Json::String(kind.to_string())),
+            ("text", Json::String(text.to_string())),
+        ])
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            // JSON has no infinities or NaN.
+            Json::Number(number) if !number.is_finite() => write!(f, "null"),
+            Json::Number(number) => write!(f, "{}", number),
+            Json::String(string) => write_string(f, string),
+            Json::Array(elements) => {
+                write!(f, "[")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(members) => {
+                write!(f, "{{")?;
+                for (index, (name, value)) in members.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, name)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, string: &str) -> fmt::Result {
+    write!(f, "\"")?;
+
+    for c in string.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+
+    write!(f, "\"")
+}
+
+#[test]
+fn test_json_display() {
+    let json = Json::Object(vec![
+        ("null", Json::Null),
+        ("numbers", Json::Object(vec![("int", Json::Number(42.0)), ("frac", Json::Number(-0.5)), ("nan", Json::Number(f64::NAN))])),
+        ("string", Json::String("say \"hi\"\\\n\t\u{1}é".to_string())),
+        ("arrays", Json::Array(vec![Json::Array(Vec::new()), Json::Number(1.0), Json::Null])),
+    ]);
+
+    assert_eq!(
+        json.to_string(),
+        r#"{"null":null,"numbers":{"int":42,"frac":-0.5,"nan":null},"string":"say \"hi\"\\\n\t\u0001é","arrays":[[],1,null]}"#
+    );
+}
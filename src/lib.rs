@@ -1 +1,8 @@
-mod tokenize;
+pub mod tokenize;
+pub mod parse;
+pub mod resolve;
+pub mod interpret;
+pub mod bytecode;
+pub mod repl;
+pub mod diagnostics;
+pub mod json;
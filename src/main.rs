@@ -1,59 +1,539 @@
 use std::env;
+use std::fmt;
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::thread;
 
-mod tokenize;
-use tokenize::tokenize;
+use interpreter_starter_rust::bytecode::{compile, compile_with_warnings, disassemble, Heap, Vm};
+use interpreter_starter_rust::diagnostics::{Code, Diagnostic, Renderer, Severity, CODES};
+use interpreter_starter_rust::interpret::Interpreter;
+use interpreter_starter_rust::json::{Json, OutputLines};
+use interpreter_starter_rust::parse::{parse, parse_expression};
+use interpreter_starter_rust::repl::{LoxHelper, Reply, Session, SessionError};
+use interpreter_starter_rust::resolve::{resolve_with_warnings, ResolveWarning};
+use interpreter_starter_rust::tokenize::{tokenize, Lexer, TokenType, TokenizerError};
+
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::Editor;
+
+// Deep Lox recursion needs far more native stack than the main thread has,
+// see `MAX_CALL_DEPTH`.
+const STACK_SIZE: usize = 1024 * 1024 * 1024;
 
 fn main() {
+    let interpreter = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run_command)
+        .unwrap();
+
+    if interpreter.join().is_err() {
+        std::process::exit(101);
+    }
+}
+
+/// How the `run` command executes a program.
+enum Backend {
+    TreeWalk,
+    Vm,
+}
+
+/// How errors are printed as text, see `Reporter`.
+enum DiagnosticStyle {
+    Plain,
+    Pretty,
+}
+
+/// What commands print on stdout: text, or JSON records as described in
+/// the `json` module.
+enum Format {
+    Text,
+    Json,
+}
+
+fn run_command() {
     let args: Vec<String> = env::args().collect();
-    if args.len() < 3 {
-        writeln!(io::stderr(), "Usage: {} tokenize <filename>", args[0]).unwrap();
+
+    // Options look like `--name=value`, or `--name` for switches, and may
+    // appear anywhere after the command.
+    let (options, operands): (Vec<&str>, Vec<&str>) = args
+        .iter()
+        .skip(1)
+        .map(|arg| arg.as_str())
+        .partition(|arg| arg.starts_with("--"));
+
+    let backend = match option_value(&options, "backend") {
+        None | Some("tree-walk") => Backend::TreeWalk,
+        Some("vm") => Backend::Vm,
+        Some(other) => {
+            eprintln!("Unknown backend: {}", other);
+            std::process::exit(64);
+        }
+    };
+
+    let style = match option_value(&options, "diagnostics") {
+        None | Some("plain") => DiagnosticStyle::Plain,
+        Some("pretty") => DiagnosticStyle::Pretty,
+        Some(other) => {
+            eprintln!("Unknown diagnostics style: {}", other);
+            std::process::exit(64);
+        }
+    };
+
+    let format = match option_value(&options, "format") {
+        None | Some("text") => Format::Text,
+        Some("json") => Format::Json,
+        Some(other) => {
+            eprintln!("Unknown format: {}", other);
+            std::process::exit(64);
+        }
+    };
+
+    let allowed = allowed_codes(&options);
+
+    // Without a command, start the REPL.
+    if operands.first().is_none_or(|&command| command == "repl") {
+        if let Backend::Vm = backend {
+            eprintln!("The REPL only runs on the tree-walk backend.");
+            std::process::exit(64);
+        }
+
+        repl(&style, &format);
         return;
     }
 
-    let command = &args[1];
-    let filename = &args[2];
+    if operands[0] == "explain" {
+        explain(&format, operands.get(1).copied());
+        return;
+    }
 
-    match command.as_str() {
+    if operands.len() < 2 {
+        eprintln!("Usage: {} [repl | explain [<code>] | <tokenize|parse|evaluate|run|disassemble> [--backend=tree-walk|vm] [--gc-stress] [--gc-log] [--diagnostics=plain|pretty] [--allow=<code>,...] [--format=text|json] <filename>]", args[0]);
+        return;
+    }
+
+    let command = operands[0];
+    let filename = operands[1];
+
+    match command {
         "tokenize" => {
             // You can use print statements as follows for debugging, they'll be visible when running tests.
-            writeln!(io::stderr(), "Logs from your program will appear here!").unwrap();
+            eprintln!("Logs from your program will appear here!");
 
-            let file_contents = fs::read_to_string(filename).unwrap_or_else(|_| {
-                writeln!(io::stderr(), "Failed to read file {}", filename).unwrap();
-                String::new()
-            });
+            let file_contents = read_file(filename);
+            let reporter = Reporter::new(&style, &format, filename, &file_contents);
+
+            // JSON records keep tokens and errors in source order.
+            if let Format::Json = format {
+                let mut exit_code = 0;
 
-            let (tokens, token_errors) = tokenize(file_contents);
+                for result in Lexer::new(&file_contents) {
+                    match result {
+                        Ok(token) if token.token_type() == &TokenType::Error => {}
+                        Ok(token) => println!("{}", Json::from(&token)),
+                        Err(token_error) => {
+                            reporter.report(&token_error);
+                            exit_code = 65;
+                        }
+                    }
+                }
+
+                std::process::exit(exit_code);
+            }
+
+            let (tokens, token_errors) = tokenize(&file_contents);
             let mut exit_code = 0; // codigo de salida correcto
 
             // imprimiendo en la salida estandar los tokens
             let tokens_msg = tokens
                 .iter()
+                .filter(|token| token.token_type() != &TokenType::Error)
                 .map(|token| token.to_string())
                 .collect::<Vec<String>>()
                 .join("\n");
             println!("{}", tokens_msg);
 
             // imprimiendo en stderr los errores
-            let token_errors_msg = token_errors
-                .iter()
-                .map(|token_error| token_error.to_string())
-                .collect::<Vec<String>>()
-                .join("\n");
-
-            if !token_errors_msg.is_empty() {
-                eprintln!("{}", token_errors_msg);
+            if report_token_errors(&reporter, &token_errors) {
                 exit_code = 65;
             }
 
             std::process::exit(exit_code);
 
         }
+        "parse" => {
+            let file_contents = read_file(filename);
+            let reporter = Reporter::new(&style, &format, filename, &file_contents);
+
+            let (tokens, token_errors) = tokenize(&file_contents);
+            if report_token_errors(&reporter, &token_errors) {
+                std::process::exit(65);
+            }
+
+            match parse_expression(tokens) {
+                Ok(expr) => print_result(&format, "expression", &expr.to_string()),
+                Err(parse_error) => {
+                    reporter.report(&parse_error);
+                    std::process::exit(65);
+                }
+            }
+        }
+        "evaluate" => {
+            let file_contents = read_file(filename);
+            let reporter = Reporter::new(&style, &format, filename, &file_contents);
+
+            let (tokens, token_errors) = tokenize(&file_contents);
+            if report_token_errors(&reporter, &token_errors) {
+                std::process::exit(65);
+            }
+
+            let expr = parse_expression(tokens).unwrap_or_else(|parse_error| {
+                reporter.report(&parse_error);
+                std::process::exit(65);
+            });
+
+            match Interpreter::new().evaluate(&expr) {
+                Ok(value) => print_result(&format, "value", &value.to_string()),
+                Err(runtime_error) => {
+                    reporter.report(&runtime_error);
+                    std::process::exit(70);
+                }
+            }
+        }
+        "run" => {
+            let file_contents = read_file(filename);
+            let reporter = Reporter::new(&style, &format, filename, &file_contents);
+
+            let (tokens, token_errors) = tokenize(&file_contents);
+            if report_token_errors(&reporter, &token_errors) {
+                std::process::exit(65);
+            }
+
+            let runtime_result = match backend {
+                Backend::TreeWalk => {
+                    let statements = parse(tokens).unwrap_or_else(|parse_errors| {
+                        for parse_error in parse_errors {
+                            reporter.report(&parse_error);
+                        }
+                        std::process::exit(65);
+                    });
+
+                    let (locals, warnings) = resolve_with_warnings(&statements).unwrap_or_else(|resolve_errors| {
+                        for resolve_error in resolve_errors {
+                            reporter.report(&resolve_error);
+                        }
+                        std::process::exit(65);
+                    });
+                    report_warnings(&reporter, &allowed, &warnings);
+
+                    let mut interpreter = Interpreter::with_output(program_output(&format));
+                    interpreter.resolve(locals);
+                    interpreter.interpret(&statements)
+                }
+                Backend::Vm => {
+                    let mut vm = Vm::with_output(program_output(&format));
+                    if has_flag(&options, "gc-stress") {
+                        vm = vm.with_gc_stress();
+                    }
+                    if has_flag(&options, "gc-log") {
+                        vm = vm.with_gc_log();
+                    }
+
+                    let (function, warnings) = compile_with_warnings(tokens, vm.heap_mut()).unwrap_or_else(|compile_errors| {
+                        for compile_error in compile_errors {
+                            reporter.report(&compile_error);
+                        }
+                        std::process::exit(65);
+                    });
+                    report_warnings(&reporter, &allowed, &warnings);
+
+                    vm.interpret(function)
+                }
+            };
+
+            if let Err(runtime_error) = runtime_result {
+                reporter.report(&runtime_error);
+                std::process::exit(70);
+            }
+        }
+        "disassemble" => {
+            let file_contents = read_file(filename);
+            let reporter = Reporter::new(&style, &format, filename, &file_contents);
+
+            let (tokens, token_errors) = tokenize(&file_contents);
+            if report_token_errors(&reporter, &token_errors) {
+                std::process::exit(65);
+            }
+
+            let mut heap = Heap::new();
+            let function = compile(tokens, &mut heap).unwrap_or_else(|compile_errors| {
+                for compile_error in compile_errors {
+                    reporter.report(&compile_error);
+                }
+                std::process::exit(65);
+            });
+
+            let listing = disassemble(function, &heap);
+            match format {
+                Format::Text => print!("{}", listing),
+                Format::Json => println!("{}", Json::text("disassembly", &listing)),
+            }
+        }
         _ => {
-            writeln!(io::stderr(), "Unknown command: {}", command).unwrap();
-            return;
+            eprintln!("Unknown command: {}", command);
         }
     }
 }
+
+/// Prints the long-form description of the diagnostic code `query`, an
+/// id like `L0001` or a name like `unexpected-character`. Without one,
+/// lists every code, with their descriptions for JSON.
+fn explain(format: &Format, query: Option<&str>) {
+    let Some(query) = query else {
+        for code in CODES {
+            match format {
+                Format::Text => println!("{}", code),
+                Format::Json => println!("{}", Json::from(code)),
+            }
+        }
+        return;
+    };
+
+    match Code::lookup(query) {
+        Some(code) => match format {
+            Format::Text => {
+                println!("{} ({} {})", code, code.phase(), code.severity());
+                println!();
+                println!("{}", code.explanation());
+            }
+            Format::Json => println!("{}", Json::from(code)),
+        },
+        None => {
+            eprintln!("Unknown diagnostic code: {}", query);
+            std::process::exit(64);
+        }
+    }
+}
+
+/// Reads and runs lines from stdin, with line editing when it's a
+/// terminal, until it ends or `:quit` is entered. Values of bare
+/// expressions and meta-command output are shown on stdout, errors are
+/// reported like those of the other commands. Errors of meta-commands
+/// themselves are always plain text on stderr.
+fn repl(style: &DiagnosticStyle, format: &Format) {
+    let mut session = Session::with_output(program_output(format));
+    let mut editor = Editor::<LoxHelper, DefaultHistory>::new().unwrap_or_else(|error| {
+        eprintln!("Failed to start the line editor: {}", error);
+        std::process::exit(74);
+    });
+    editor.set_helper(Some(LoxHelper::new()));
+
+    let history = history_path();
+    if let Some(history) = &history {
+        // There is no history yet on the first run.
+        let _ = editor.load_history(history);
+    }
+
+    loop {
+        if let Some(helper) = editor.helper_mut() {
+            helper.set_globals(session.global_names());
+        }
+
+        let prompt = if session.is_continuing() { "... " } else { "> " };
+        let line = match editor.readline(prompt) {
+            Ok(line) => line,
+            // Ctrl-C drops the input being typed, Ctrl-D ends the session.
+            Err(ReadlineError::Interrupted) => {
+                session.cancel();
+                continue;
+            }
+            Err(_) => break,
+        };
+
+        if !line.trim().is_empty() {
+            let _ = editor.add_history_entry(line.as_str());
+        }
+
+        match session.feed(&line) {
+            Reply::Incomplete | Reply::Done(None) => {}
+            Reply::Done(Some(value)) => print_result(format, "value", &value.to_string()),
+            Reply::Errors(errors) => {
+                for error in errors {
+                    match &error {
+                        SessionError::Source { diagnostic, file_name, source, .. } => {
+                            Reporter::new(style, format, file_name, source).report_diagnostic(&error, diagnostic);
+                        }
+                        SessionError::Command(_) => eprintln!("{}", error),
+                    }
+                }
+            }
+            Reply::Text(text) => match format {
+                Format::Text => println!("{}", text),
+                Format::Json => {
+                    for line in text.lines() {
+                        println!("{}", Json::text("output", line));
+                    }
+                }
+            },
+            Reply::Quit => break,
+        }
+    }
+
+    if let Some(history) = &history {
+        if let Err(error) = editor.save_history(history) {
+            eprintln!("Failed to save history to {}: {}", history.display(), error);
+        }
+    }
+}
+
+/// Where the REPL keeps its history across sessions, `~/.lox_history`.
+/// Only sessions on a terminal have one, so piping a script in leaves no
+/// trace.
+fn history_path() -> Option<PathBuf> {
+    if !io::stdin().is_terminal() {
+        return None;
+    }
+
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".lox_history"))
+}
+
+/// Prints the result of a command, as a `kind` record in JSON.
+fn print_result(format: &Format, kind: &str, text: &str) {
+    match format {
+        Format::Text => println!("{}", text),
+        Format::Json => println!("{}", Json::text(kind, text)),
+    }
+}
+
+/// Where the `print` statements of a program go.
+fn program_output(format: &Format) -> Box<dyn Write> {
+    match format {
+        Format::Text => Box::new(io::stdout()),
+        Format::Json => Box::new(OutputLines::new(io::stdout())),
+    }
+}
+
+/// The value of the option `--name=value`, if it was given.
+fn option_value<'a>(options: &[&'a str], name: &str) -> Option<&'a str> {
+    options.iter().find_map(|option| {
+        option
+            .strip_prefix("--")?
+            .strip_prefix(name)?
+            .strip_prefix('=')
+    })
+}
+
+/// The codes of the warnings and notes turned off with
+/// `--allow=<code>,...`, given by id or name.
+fn allowed_codes(options: &[&str]) -> Vec<&'static Code> {
+    let Some(queries) = option_value(options, "allow") else {
+        return Vec::new();
+    };
+
+    queries
+        .split(',')
+        .map(|query| match Code::lookup(query) {
+            Some(code) if code.severity() != Severity::Error => code,
+            Some(code) => {
+                eprintln!("Only warnings and notes can be allowed: {}", code);
+                std::process::exit(64);
+            }
+            None => {
+                eprintln!("Unknown diagnostic code: {}", query);
+                std::process::exit(64);
+            }
+        })
+        .collect()
+}
+
+/// Whether the switch `--name` was given.
+fn has_flag(options: &[&str], name: &str) -> bool {
+    options.iter().any(|option| option.strip_prefix("--") == Some(name))
+}
+
+fn read_file(filename: &str) -> String {
+    fs::read_to_string(filename).unwrap_or_else(|_| {
+        eprintln!("Failed to read file {}", filename);
+        String::new()
+    })
+}
+
+/// Prints the tokenizer errors to stderr, returning whether there were any.
+fn report_token_errors(reporter: &Reporter<'_>, token_errors: &[TokenizerError]) -> bool {
+    for token_error in token_errors {
+        reporter.report(token_error);
+    }
+
+    !token_errors.is_empty()
+}
+
+/// Shows the warnings and notes found before running a program, except
+/// the allowed ones.
+fn report_warnings(reporter: &Reporter<'_>, allowed: &[&Code], warnings: &[ResolveWarning]) {
+    for warning in warnings {
+        if !allowed.contains(&warning.code()) {
+            reporter.show(&warning.into());
+        }
+    }
+}
+
+/// Prints errors: to stderr as the plain `[line N] Error: ...` lines by
+/// default, or with `--diagnostics=pretty` rendered under the source they
+/// refer to, in colour when stderr is a terminal. With `--format=json`
+/// they are diagnostic records on stdout instead.
+///
+/// Plain and pretty text both go to `diagnostics_output`.
+enum Reporter<'src> {
+    Plain,
+    Pretty(Renderer<'src>),
+    Json,
+}
+
+impl<'src> Reporter<'src> {
+    fn new(style: &DiagnosticStyle, format: &Format, filename: &'src str, source: &'src str) -> Self {
+        match (format, style) {
+            (Format::Json, _) => Reporter::Json,
+            (Format::Text, DiagnosticStyle::Plain) => Reporter::Plain,
+            (Format::Text, DiagnosticStyle::Pretty) if diagnostics_output().is_terminal() => {
+                Reporter::Pretty(Renderer::new(filename, source).with_colour())
+            }
+            (Format::Text, DiagnosticStyle::Pretty) => Reporter::Pretty(Renderer::new(filename, source)),
+        }
+    }
+
+    fn report<'e, E>(&self, error: &'e E)
+    where
+        E: fmt::Display,
+        Diagnostic: From<&'e E>,
+    {
+        self.report_diagnostic(error, &error.into());
+    }
+
+    /// Reports `diagnostic`, whose plain text is `error`.
+    fn report_diagnostic(&self, error: &dyn fmt::Display, diagnostic: &Diagnostic) {
+        match self {
+            Reporter::Plain => writeln!(diagnostics_output(), "{}", error).expect("failed to write a diagnostic"),
+            Reporter::Pretty(_) | Reporter::Json => self.show(diagnostic),
+        }
+    }
+
+    /// Reports `diagnostic` rendered or as JSON. Plain output is the
+    /// reference interpreter's, which only has errors in their own text,
+    /// so it leaves out what has no such text, like warnings.
+    fn show(&self, diagnostic: &Diagnostic) {
+        match self {
+            Reporter::Plain => Ok(()),
+            // Pretty diagnostics are set apart by a blank line.
+            Reporter::Pretty(renderer) => writeln!(diagnostics_output(), "{}", renderer.render(diagnostic)),
+            Reporter::Json => writeln!(io::stdout(), "{}", Json::from(diagnostic)),
+        }
+        .expect("failed to write a diagnostic");
+    }
+}
+
+/// The stream text diagnostics are written to, whose being a terminal
+/// decides whether they are coloured.
+fn diagnostics_output() -> io::Stderr {
+    io::stderr()
+}
@@ -0,0 +1,41 @@
+use crate::tokenize::{Span, Token, TokenType};
+
+/// A token as kept in the syntax tree. Unlike `Token` it owns its text, so
+/// the tree can outlive the source it was parsed from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AstToken {
+    token_type: TokenType,
+    lexeme: String,
+    line: u32,
+    span: Option<Span>,
+}
+
+impl AstToken {
+    pub fn token_type(&self) -> &TokenType {
+        &self.token_type
+    }
+
+    /// For identifiers this is the NFC name, see `Token::name`.
+    pub fn lexeme(&self) -> &str {
+        &self.lexeme
+    }
+
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+}
+
+impl From<&Token<'_>> for AstToken {
+    fn from(token: &Token<'_>) -> Self {
+        Self {
+            token_type: token.token_type().clone(),
+            lexeme: token.name().to_string(),
+            line: token.line(),
+            span: token.span(),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use super::ast_token::AstToken;
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Identifies an expression that refers to a variable, so that later passes
+/// can attach information to it, such as the scope it resolves to. Ids are
+/// unique within the process, even across separate parses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExprId(usize);
+
+impl ExprId {
+    pub fn next() -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Literal),
+    Grouping(Box<Expr>),
+    Unary {
+        operator: AstToken,
+        right: Box<Expr>,
+    },
+    Binary {
+        left: Box<Expr>,
+        operator: AstToken,
+        right: Box<Expr>,
+    },
+    // `and` / `or`, which short-circuit unlike `Binary`.
+    Logical {
+        left: Box<Expr>,
+        operator: AstToken,
+        right: Box<Expr>,
+    },
+    Variable {
+        id: ExprId,
+        name: AstToken,
+    },
+    Assign {
+        id: ExprId,
+        name: AstToken,
+        value: Box<Expr>,
+    },
+    Call {
+        callee: Box<Expr>,
+        // The closing parenthesis, where call errors are reported.
+        paren: AstToken,
+        arguments: Vec<Expr>,
+    },
+    // Property access, `object.name`.
+    Get {
+        object: Box<Expr>,
+        name: AstToken,
+    },
+    Set {
+        object: Box<Expr>,
+        name: AstToken,
+        value: Box<Expr>,
+    },
+    This {
+        id: ExprId,
+        keyword: AstToken,
+    },
+    Super {
+        id: ExprId,
+        keyword: AstToken,
+        method: AstToken,
+    },
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Nil => write!(f, "nil"),
+            Literal::Bool(value) => write!(f, "{}", value),
+            Literal::Number(value) => write!(f, "{:?}", value),
+            Literal::String(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+// Prints the tree in the parenthesised, Lisp-like form of the `parse`
+// command, e.g. `(+ 1.0 (group (* 2.0 3.0)))`.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Literal(literal) => write!(f, "{}", literal),
+            Expr::Grouping(expression) => write!(f, "(group {})", expression),
+            Expr::Unary { operator, right } => {
+                write!(f, "({} {})", operator.lexeme(), right)
+            }
+            Expr::Binary { left, operator, right }
+            | Expr::Logical { left, operator, right } => {
+                write!(f, "({} {} {})", operator.lexeme(), left, right)
+            }
+            Expr::Variable { name, .. } => write!(f, "{}", name.lexeme()),
+            Expr::Assign { name, value, .. } => {
+                write!(f, "(= {} {})", name.lexeme(), value)
+            }
+            Expr::Call { callee, arguments, .. } => {
+                write!(f, "(call {}", callee)?;
+                for argument in arguments {
+                    write!(f, " {}", argument)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Get { object, name } => write!(f, "(. {} {})", object, name.lexeme()),
+            Expr::Set { object, name, value } => {
+                write!(f, "(= (. {} {}) {})", object, name.lexeme(), value)
+            }
+            Expr::This { .. } => write!(f, "this"),
+            Expr::Super { method, .. } => write!(f, "super.{}", method.lexeme()),
+        }
+    }
+}
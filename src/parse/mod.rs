@@ -0,0 +1,163 @@
+mod ast_token;
+pub use ast_token::AstToken;
+
+mod expr;
+pub use expr::{Expr, ExprId, Literal};
+
+mod stmt;
+pub use stmt::{FunctionDecl, Stmt};
+
+mod parse_error;
+pub use parse_error::ParseError;
+
+mod parser;
+pub use parser::Parser;
+
+use crate::tokenize::Token;
+
+/// Parses a single expression out of the tokens produced by `tokenize`.
+pub fn parse_expression(tokens: Vec<Token<'_>>) -> Result<Expr, ParseError> {
+    Parser::new(tokens).parse_expression()
+}
+
+/// Parses a whole program, returning every syntax error found.
+pub fn parse(tokens: Vec<Token<'_>>) -> Result<Vec<Stmt>, Vec<ParseError>> {
+    Parser::new(tokens).parse()
+}
+
+#[cfg(test)]
+use crate::tokenize::tokenize;
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[cfg(test)]
+fn parse_to_string(file_contents: &str) -> String {
+    let (tokens, _) = tokenize(file_contents);
+
+    match parse_expression(tokens) {
+        Ok(expr) => expr.to_string(),
+        Err(parse_error) => parse_error.to_string(),
+    }
+}
+
+#[test]
+fn test_parse_literals() {
+    assert_eq!(parse_to_string("true"), "true");
+    assert_eq!(parse_to_string("nil"), "nil");
+    assert_eq!(parse_to_string("42"), "42.0");
+    assert_eq!(parse_to_string("0.5"), "0.5");
+    assert_eq!(parse_to_string("\"hello world\""), "hello world");
+}
+
+#[test]
+fn test_parse_grouping_and_unary() {
+    assert_eq!(parse_to_string("(\"foo\")"), "(group foo)");
+    assert_eq!(parse_to_string("((true))"), "(group (group true))");
+    assert_eq!(parse_to_string("!!false"), "(! (! false))");
+    assert_eq!(parse_to_string("-(-3)"), "(- (group (- 3.0)))");
+}
+
+#[test]
+fn test_parse_binary_precedence() {
+    assert_eq!(parse_to_string("1 + 2 * 3"), "(+ 1.0 (* 2.0 3.0))");
+    assert_eq!(parse_to_string("(1 + 2) * 3"), "(* (group (+ 1.0 2.0)) 3.0)");
+    assert_eq!(parse_to_string("8 / 4 / 2"), "(/ (/ 8.0 4.0) 2.0)");
+    assert_eq!(parse_to_string("1 - -2 < 3"), "(< (- 1.0 (- 2.0)) 3.0)");
+    assert_eq!(
+        parse_to_string("1 == 2 != 3 >= 4"),
+        "(!= (== 1.0 2.0) (>= 3.0 4.0))"
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(parse_to_string("(72 +)"), "[line 1] Error at ')': Expect expression.");
+    assert_eq!(parse_to_string("(1\n"), "[line 2] Error at end: Expect ')' after expression.");
+    assert_eq!(parse_to_string(""), "[line 1] Error at end: Expect expression.");
+}
+
+#[test]
+fn test_parse_skips_error_tokens() {
+    assert_eq!(parse_to_string("1 + $ 2"), "(+ 1.0 2.0)");
+}
+
+#[cfg(test)]
+fn parse_errors(file_contents: &str) -> Vec<String> {
+    let (tokens, _) = tokenize(file_contents);
+
+    match parse(tokens) {
+        Ok(_) => Vec::new(),
+        Err(parse_errors) => parse_errors
+            .iter()
+            .map(|parse_error| parse_error.to_string())
+            .collect(),
+    }
+}
+
+#[test]
+fn test_parse_logical_and_assignment() {
+    assert_eq!(parse_to_string("a = b or c and d"), "(= a (or b (and c d)))");
+    assert_eq!(parse_to_string("a = b = 1"), "(= a (= b 1.0))");
+    assert_eq!(
+        parse_to_string("a + b = c"),
+        "[line 1] Error at '=': Invalid assignment target."
+    );
+}
+
+#[test]
+fn test_parse_statements() {
+    let (tokens, _) = tokenize("var a = 1; { print a; } if (a) a = 2; else print 3; while (false) a;");
+    let statements = parse(tokens).unwrap();
+
+    assert_eq!(statements.len(), 4);
+    assert!(matches!(statements[0], Stmt::Var { .. }));
+    assert!(matches!(&statements[1], Stmt::Block(block) if block.len() == 1));
+    assert!(matches!(statements[2], Stmt::If { else_branch: Some(_), .. }));
+    assert!(matches!(statements[3], Stmt::While { .. }));
+}
+
+#[test]
+fn test_parse_classes_and_properties() {
+    let (tokens, _) = tokenize("class B < A { init(x) { this.x = x; } get() { return super.get(); } }");
+    let statements = parse(tokens).unwrap();
+
+    let Stmt::Class { name, superclass: Some(superclass), methods } = &statements[0] else {
+        panic!("expected a class with a superclass");
+    };
+    assert_eq!(name.lexeme(), "B");
+    assert_eq!(superclass.to_string(), "A");
+    assert_eq!(methods.len(), 2);
+    assert_eq!(methods[0].name.lexeme(), "init");
+
+    assert_eq!(parse_to_string("a.b.c"), "(. (. a b) c)");
+    assert_eq!(parse_to_string("a.b(1).c = 2"), "(= (. (call (. a b) 1.0) c) 2.0)");
+}
+
+#[test]
+fn test_parse_for_desugars_to_while() {
+    let (tokens, _) = tokenize("for (var i = 0; i < 3; i = i + 1) print i;");
+    let statements = parse(tokens).unwrap();
+
+    let Stmt::Block(block) = &statements[0] else {
+        panic!("expected the initializer block, got {:?}", statements[0]);
+    };
+    assert!(matches!(block[0], Stmt::Var { .. }));
+    assert!(matches!(&block[1], Stmt::While { body, .. } if matches!(**body, Stmt::Block(_))));
+}
+
+#[test]
+fn test_parse_reports_every_error() {
+    assert_eq!(
+        parse_errors("var = 1;\nprint 2\nvar b = (;\n{ print 1;"),
+        vec![
+            "[line 1] Error at '=': Expect variable name.",
+            "[line 3] Error at 'var': Expect ';' after value.",
+            "[line 4] Error at end: Expect '}' after block.",
+        ]
+    );
+    assert_eq!(
+        parse_errors("for (;;) var x;"),
+        vec!["[line 1] Error at 'var': Expect expression."]
+    );
+}
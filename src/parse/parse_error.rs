@@ -0,0 +1,87 @@
+use crate::diagnostics::Code;
+use crate::tokenize::Span;
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    code: &'static Code,
+    line: u32,
+    span: Option<Span>,
+    // The offending lexeme, `None` when the error is at the end of input.
+    lexeme: Option<String>,
+    message: String,
+}
+
+impl ParseError {
+    pub fn new(code: &'static Code, line: u32, span: Option<Span>, lexeme: Option<String>, message: String) -> Self {
+        Self {
+            code,
+            line,
+            span,
+            lexeme,
+            message,
+        }
+    }
+
+    pub fn code(&self) -> &'static Code {
+        self.code
+    }
+
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    pub fn lexeme(&self) -> Option<&str> {
+        self.lexeme.as_deref()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.lexeme {
+            Some(lexeme) => write!(
+                f,
+                "[line {}] Error at '{}': {}",
+                self.line,
+                lexeme,
+                self.message
+            ),
+            None => write!(
+                f,
+                "[line {}] Error at end: {}",
+                self.line,
+                self.message
+            ),
+        }
+    }
+}
+
+#[test]
+fn test_parse_error_display() {
+    let parse_error = ParseError::new(
+        &Code::EXPECTED_EXPRESSION,
+        3,
+        None,
+        Some(")".to_string()),
+        "Expect expression.".to_string(),
+    );
+    assert_eq!(parse_error.to_string(), "[line 3] Error at ')': Expect expression.");
+
+    let parse_error = ParseError::new(
+        &Code::EXPECTED_TOKEN,
+        1,
+        None,
+        None,
+        "Expect ')' after expression.".to_string(),
+    );
+    assert_eq!(parse_error.to_string(), "[line 1] Error at end: Expect ')' after expression.");
+}
@@ -0,0 +1,659 @@
+use crate::diagnostics::Code;
+use crate::tokenize::{LiteralType, Token, TokenType};
+use super::{AstToken, Expr, ExprId, FunctionDecl, Literal, ParseError, Stmt};
+
+use std::rc::Rc;
+
+type ParseResult<T> = Result<T, ParseError>;
+
+const MAX_ARGUMENTS: usize = 255;
+
+/// Recursive-descent parser over the tokens produced by `tokenize`.
+///
+/// Grammar, from lowest to highest precedence:
+///
+/// ```text
+/// program     → declaration* EOF ;
+/// declaration → classDecl | funDecl | varDecl | statement ;
+/// classDecl   → "class" IDENTIFIER ( "<" IDENTIFIER )? "{" function* "}" ;
+/// funDecl     → "fun" function ;
+/// function    → IDENTIFIER "(" parameters? ")" block ;
+/// parameters  → IDENTIFIER ( "," IDENTIFIER )* ;
+/// varDecl     → "var" IDENTIFIER ( "=" expression )? ";" ;
+/// statement   → exprStmt | forStmt | ifStmt | printStmt | returnStmt
+///             | whileStmt | block ;
+/// exprStmt    → expression ";" ;
+/// forStmt     → "for" "(" ( varDecl | exprStmt | ";" )
+///               expression? ";" expression? ")" statement ;
+/// ifStmt      → "if" "(" expression ")" statement ( "else" statement )? ;
+/// printStmt   → "print" expression ";" ;
+/// returnStmt  → "return" expression? ";" ;
+/// whileStmt   → "while" "(" expression ")" statement ;
+/// block       → "{" declaration* "}" ;
+///
+/// expression → assignment ;
+/// assignment → ( call "." )? IDENTIFIER "=" assignment | logic_or ;
+/// logic_or   → logic_and ( "or" logic_and )* ;
+/// logic_and  → equality ( "and" equality )* ;
+/// equality   → comparison ( ( "!=" | "==" ) comparison )* ;
+/// comparison → term ( ( ">" | ">=" | "<" | "<=" ) term )* ;
+/// term       → factor ( ( "-" | "+" ) factor )* ;
+/// factor     → unary ( ( "/" | "*" ) unary )* ;
+/// unary      → ( "!" | "-" ) unary | call ;
+/// call       → primary ( "(" arguments? ")" | "." IDENTIFIER )* ;
+/// arguments  → expression ( "," expression )* ;
+/// primary    → NUMBER | STRING | "true" | "false" | "nil" | "this"
+///            | "(" expression ")" | IDENTIFIER
+///            | "super" "." IDENTIFIER ;
+/// ```
+#[derive(Debug)]
+pub struct Parser<'src> {
+    tokens: Vec<Token<'src>>,
+    current: usize,
+    // Errors reported without unwinding, such as an invalid assignment
+    // target, plus the ones recovered from by `synchronize`.
+    errors: Vec<ParseError>,
+}
+
+impl<'src> Parser<'src> {
+    /// `Error` tokens are dropped: the lexer already reported them, and the
+    /// parser carries on with the tokens around them.
+    pub fn new(tokens: Vec<Token<'src>>) -> Self {
+        let mut tokens: Vec<Token<'src>> = tokens
+            .into_iter()
+            .filter(|token| token.token_type() != &TokenType::Error)
+            .collect();
+
+        if tokens.last().map(|token| token.token_type()) != Some(&TokenType::Eof) {
+            let line = tokens.last().map_or(1, |token| token.line());
+            tokens.push(Token::new(TokenType::Eof, "", None, line));
+        }
+
+        Self {
+            tokens,
+            current: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn parse_expression(&mut self) -> ParseResult<Expr> {
+        let expr = self.expression()?;
+
+        match self.errors.first() {
+            Some(parse_error) => Err(parse_error.clone()),
+            None => Ok(expr),
+        }
+    }
+
+    /// Parses a whole program. On a syntax error the parser skips ahead to
+    /// the next statement and keeps going, so every error is reported.
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, Vec<ParseError>> {
+        let mut statements = Vec::new();
+
+        while !self.is_at_end() {
+            if let Some(statement) = self.declaration() {
+                statements.push(statement);
+            }
+        }
+
+        if !self.errors.is_empty() {
+            return Err(std::mem::take(&mut self.errors));
+        }
+
+        Ok(statements)
+    }
+
+    fn declaration(&mut self) -> Option<Stmt> {
+        let statement = if self.match_any(&[TokenType::Class]) {
+            self.class_declaration()
+        } else if self.match_any(&[TokenType::Fun]) {
+            self.function("function").map(Stmt::Function)
+        } else if self.match_any(&[TokenType::Var]) {
+            self.var_declaration()
+        } else {
+            self.statement()
+        };
+
+        match statement {
+            Ok(statement) => Some(statement),
+            Err(parse_error) => {
+                self.errors.push(parse_error);
+                self.synchronize();
+                None
+            }
+        }
+    }
+
+    fn class_declaration(&mut self) -> ParseResult<Stmt> {
+        let name = AstToken::from(self.consume(TokenType::Identifier, "Expect class name.")?);
+
+        let superclass = match self.match_any(&[TokenType::Less]) {
+            true => Some(Expr::Variable {
+                id: ExprId::next(),
+                name: AstToken::from(
+                    self.consume(TokenType::Identifier, "Expect superclass name.")?
+                ),
+            }),
+            false => None,
+        };
+
+        self.consume(TokenType::LeftBrace, "Expect '{' before class body.")?;
+
+        let mut methods = Vec::new();
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            methods.push(self.function("method")?);
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after class body.")?;
+
+        Ok(Stmt::Class {
+            name,
+            superclass,
+            methods,
+        })
+    }
+
+    /// Parses a function's name, parameters and body. `kind` names it in
+    /// error messages.
+    fn function(&mut self, kind: &str) -> ParseResult<Rc<FunctionDecl>> {
+        let name = AstToken::from(
+            self.consume(TokenType::Identifier, &format!("Expect {} name.", kind))?
+        );
+        self.consume(TokenType::LeftParen, &format!("Expect '(' after {} name.", kind))?;
+
+        let mut params = Vec::new();
+        if !self.check(&TokenType::RightParen) {
+            loop {
+                if params.len() >= MAX_ARGUMENTS {
+                    let parse_error = self.error(&Code::TOO_MANY_PARAMETERS, self.peek(), "Can't have more than 255 parameters.");
+                    self.errors.push(parse_error);
+                }
+
+                params.push(AstToken::from(
+                    self.consume(TokenType::Identifier, "Expect parameter name.")?
+                ));
+
+                if !self.match_any(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(TokenType::RightParen, "Expect ')' after parameters.")?;
+
+        self.consume(TokenType::LeftBrace, &format!("Expect '{{' before {} body.", kind))?;
+        let body = self.block()?;
+
+        Ok(Rc::new(FunctionDecl { name, params, body }))
+    }
+
+    fn var_declaration(&mut self) -> ParseResult<Stmt> {
+        let name = AstToken::from(self.consume(TokenType::Identifier, "Expect variable name.")?);
+
+        let initializer = match self.match_any(&[TokenType::Equal]) {
+            true => Some(self.expression()?),
+            false => None,
+        };
+
+        self.consume(TokenType::Semicolon, "Expect ';' after variable declaration.")?;
+
+        Ok(Stmt::Var { name, initializer })
+    }
+
+    fn statement(&mut self) -> ParseResult<Stmt> {
+        match self.peek().token_type() {
+            TokenType::For => {
+                self.advance();
+                self.for_statement()
+            }
+            TokenType::If => {
+                self.advance();
+                self.if_statement()
+            }
+            TokenType::Print => {
+                self.advance();
+                self.print_statement()
+            }
+            TokenType::Return => {
+                self.advance();
+                self.return_statement()
+            }
+            TokenType::While => {
+                self.advance();
+                self.while_statement()
+            }
+            TokenType::LeftBrace => {
+                self.advance();
+                Ok(Stmt::Block(self.block()?))
+            }
+            _ => self.expression_statement(),
+        }
+    }
+
+    /// Desugars `for (init; condition; increment) body` into
+    /// `{ init; while (condition) { body; increment; } }`.
+    fn for_statement(&mut self) -> ParseResult<Stmt> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
+
+        let initializer = if self.match_any(&[TokenType::Semicolon]) {
+            None
+        } else if self.match_any(&[TokenType::Var]) {
+            Some(self.var_declaration()?)
+        } else {
+            Some(self.expression_statement()?)
+        };
+
+        let condition = match self.check(&TokenType::Semicolon) {
+            true => Expr::Literal(Literal::Bool(true)),
+            false => self.expression()?,
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after loop condition.")?;
+
+        let increment = match self.check(&TokenType::RightParen) {
+            true => None,
+            false => Some(self.expression()?),
+        };
+        self.consume(TokenType::RightParen, "Expect ')' after for clauses.")?;
+
+        let mut body = self.statement()?;
+
+        if let Some(increment) = increment {
+            body = Stmt::Block(vec![body, Stmt::Expression(increment)]);
+        }
+
+        body = Stmt::While {
+            condition,
+            body: Box::new(body),
+        };
+
+        if let Some(initializer) = initializer {
+            body = Stmt::Block(vec![initializer, body]);
+        }
+
+        Ok(body)
+    }
+
+    fn if_statement(&mut self) -> ParseResult<Stmt> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'if'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after if condition.")?;
+
+        let then_branch = Box::new(self.statement()?);
+        let else_branch = match self.match_any(&[TokenType::Else]) {
+            true => Some(Box::new(self.statement()?)),
+            false => None,
+        };
+
+        Ok(Stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+        })
+    }
+
+    fn print_statement(&mut self) -> ParseResult<Stmt> {
+        let value = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after value.")?;
+
+        Ok(Stmt::Print(value))
+    }
+
+    fn return_statement(&mut self) -> ParseResult<Stmt> {
+        let keyword = AstToken::from(self.previous());
+
+        let value = match self.check(&TokenType::Semicolon) {
+            true => None,
+            false => Some(self.expression()?),
+        };
+        self.consume(TokenType::Semicolon, "Expect ';' after return value.")?;
+
+        Ok(Stmt::Return { keyword, value })
+    }
+
+    fn while_statement(&mut self) -> ParseResult<Stmt> {
+        self.consume(TokenType::LeftParen, "Expect '(' after 'while'.")?;
+        let condition = self.expression()?;
+        self.consume(TokenType::RightParen, "Expect ')' after condition.")?;
+
+        let body = Box::new(self.statement()?);
+
+        Ok(Stmt::While { condition, body })
+    }
+
+    fn block(&mut self) -> ParseResult<Vec<Stmt>> {
+        let mut statements = Vec::new();
+
+        while !self.check(&TokenType::RightBrace) && !self.is_at_end() {
+            if let Some(statement) = self.declaration() {
+                statements.push(statement);
+            }
+        }
+
+        self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+
+        Ok(statements)
+    }
+
+    fn expression_statement(&mut self) -> ParseResult<Stmt> {
+        let expr = self.expression()?;
+        self.consume(TokenType::Semicolon, "Expect ';' after expression.")?;
+
+        Ok(Stmt::Expression(expr))
+    }
+
+    fn expression(&mut self) -> ParseResult<Expr> {
+        self.assignment()
+    }
+
+    fn assignment(&mut self) -> ParseResult<Expr> {
+        let expr = self.or()?;
+
+        if self.match_any(&[TokenType::Equal]) {
+            let equals = self.previous().clone();
+            let value = self.assignment()?;
+
+            match expr {
+                Expr::Variable { name, .. } => {
+                    return Ok(Expr::Assign {
+                        id: ExprId::next(),
+                        name,
+                        value: Box::new(value),
+                    });
+                }
+                Expr::Get { object, name } => {
+                    return Ok(Expr::Set {
+                        object,
+                        name,
+                        value: Box::new(value),
+                    });
+                }
+                _ => {}
+            }
+
+            // Reported, but there is no need to synchronize: the parser is
+            // not confused about where it is.
+            let parse_error = self.error(&Code::INVALID_ASSIGNMENT_TARGET, &equals, "Invalid assignment target.");
+            self.errors.push(parse_error);
+        }
+
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> ParseResult<Expr> {
+        self.logical(TokenType::Or, Self::and)
+    }
+
+    fn and(&mut self) -> ParseResult<Expr> {
+        self.logical(TokenType::And, Self::equality)
+    }
+
+    fn logical(
+        &mut self,
+        operator: TokenType,
+        operand: fn(&mut Self) -> ParseResult<Expr>,
+    ) -> ParseResult<Expr> {
+        let mut expr = operand(self)?;
+
+        while self.match_any(std::slice::from_ref(&operator)) {
+            let operator = AstToken::from(self.previous());
+            let right = operand(self)?;
+
+            expr = Expr::Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn equality(&mut self) -> ParseResult<Expr> {
+        self.binary(
+            &[TokenType::BangEqual, TokenType::EqualEqual],
+            Self::comparison
+        )
+    }
+
+    fn comparison(&mut self) -> ParseResult<Expr> {
+        self.binary(
+            &[
+                TokenType::Greater,
+                TokenType::GreaterEqual,
+                TokenType::Less,
+                TokenType::LessEqual,
+            ],
+            Self::term
+        )
+    }
+
+    fn term(&mut self) -> ParseResult<Expr> {
+        self.binary(&[TokenType::Minus, TokenType::Plus], Self::factor)
+    }
+
+    fn factor(&mut self) -> ParseResult<Expr> {
+        self.binary(&[TokenType::Slash, TokenType::Star], Self::unary)
+    }
+
+    /// Parses a left-associative chain of `operand`s joined by any of the
+    /// `operators`.
+    fn binary(
+        &mut self,
+        operators: &[TokenType],
+        operand: fn(&mut Self) -> ParseResult<Expr>,
+    ) -> ParseResult<Expr> {
+        let mut expr = operand(self)?;
+
+        while self.match_any(operators) {
+            let operator = AstToken::from(self.previous());
+            let right = operand(self)?;
+
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> ParseResult<Expr> {
+        if self.match_any(&[TokenType::Bang, TokenType::Minus]) {
+            let operator = AstToken::from(self.previous());
+            let right = self.unary()?;
+
+            return Ok(Expr::Unary {
+                operator,
+                right: Box::new(right),
+            });
+        }
+
+        self.call()
+    }
+
+    fn call(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.primary()?;
+
+        loop {
+            if self.match_any(&[TokenType::LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.match_any(&[TokenType::Dot]) {
+                let name = AstToken::from(
+                    self.consume(TokenType::Identifier, "Expect property name after '.'.")?
+                );
+
+                expr = Expr::Get {
+                    object: Box::new(expr),
+                    name,
+                };
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> ParseResult<Expr> {
+        let mut arguments = Vec::new();
+
+        if !self.check(&TokenType::RightParen) {
+            loop {
+                if arguments.len() >= MAX_ARGUMENTS {
+                    let parse_error = self.error(&Code::TOO_MANY_ARGUMENTS, self.peek(), "Can't have more than 255 arguments.");
+                    self.errors.push(parse_error);
+                }
+
+                arguments.push(self.expression()?);
+
+                if !self.match_any(&[TokenType::Comma]) {
+                    break;
+                }
+            }
+        }
+
+        let paren = AstToken::from(
+            self.consume(TokenType::RightParen, "Expect ')' after arguments.")?
+        );
+
+        Ok(Expr::Call {
+            callee: Box::new(callee),
+            paren,
+            arguments,
+        })
+    }
+
+    fn primary(&mut self) -> ParseResult<Expr> {
+        let token = self.peek();
+
+        let literal = match token.token_type() {
+            TokenType::False => Some(Literal::Bool(false)),
+            TokenType::True => Some(Literal::Bool(true)),
+            TokenType::Nil => Some(Literal::Nil),
+            TokenType::Number | TokenType::String => match token.literal() {
+                Some(LiteralType::Number(value)) => Some(Literal::Number(*value)),
+                Some(LiteralType::String(value)) => Some(Literal::String(value.to_string())),
+                None => None,
+            },
+            _ => None,
+        };
+
+        if let Some(literal) = literal {
+            self.advance();
+            return Ok(Expr::Literal(literal));
+        }
+
+        if self.match_any(&[TokenType::This]) {
+            return Ok(Expr::This {
+                id: ExprId::next(),
+                keyword: AstToken::from(self.previous()),
+            });
+        }
+
+        if self.match_any(&[TokenType::Super]) {
+            let keyword = AstToken::from(self.previous());
+            self.consume(TokenType::Dot, "Expect '.' after 'super'.")?;
+            let method = AstToken::from(
+                self.consume(TokenType::Identifier, "Expect superclass method name.")?
+            );
+
+            return Ok(Expr::Super {
+                id: ExprId::next(),
+                keyword,
+                method,
+            });
+        }
+
+        if self.match_any(&[TokenType::Identifier]) {
+            return Ok(Expr::Variable {
+                id: ExprId::next(),
+                name: AstToken::from(self.previous()),
+            });
+        }
+
+        if self.match_any(&[TokenType::LeftParen]) {
+            let expr = self.expression()?;
+            self.consume(TokenType::RightParen, "Expect ')' after expression.")?;
+
+            return Ok(Expr::Grouping(Box::new(expr)));
+        }
+
+        Err(self.error(&Code::EXPECTED_EXPRESSION, self.peek(), "Expect expression."))
+    }
+
+    /// Discards tokens until the probable start of the next statement, so
+    /// one mistake does not cascade into many errors.
+    fn synchronize(&mut self) {
+        self.advance();
+
+        while !self.is_at_end() {
+            if self.previous().token_type() == &TokenType::Semicolon {
+                return;
+            }
+
+            match self.peek().token_type() {
+                TokenType::Class
+                | TokenType::Fun
+                | TokenType::Var
+                | TokenType::For
+                | TokenType::If
+                | TokenType::While
+                | TokenType::Print
+                | TokenType::Return => return,
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    fn match_any(&mut self, token_types: &[TokenType]) -> bool {
+        if token_types.iter().any(|token_type| self.check(token_type)) {
+            self.advance();
+            return true;
+        }
+
+        false
+    }
+
+    fn consume(&mut self, token_type: TokenType, message: &str) -> ParseResult<&Token<'src>> {
+        if self.check(&token_type) {
+            return Ok(self.advance());
+        }
+
+        Err(self.error(&Code::EXPECTED_TOKEN, self.peek(), message))
+    }
+
+    fn check(&self, token_type: &TokenType) -> bool {
+        !self.is_at_end() && self.peek().token_type() == token_type
+    }
+
+    fn advance(&mut self) -> &Token<'src> {
+        if !self.is_at_end() {
+            self.current += 1;
+        }
+
+        self.previous()
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.peek().token_type() == &TokenType::Eof
+    }
+
+    fn peek(&self) -> &Token<'src> {
+        &self.tokens[self.current]
+    }
+
+    fn previous(&self) -> &Token<'src> {
+        &self.tokens[self.current - 1]
+    }
+
+    fn error(&self, code: &'static Code, token: &Token<'src>, message: &str) -> ParseError {
+        let lexeme = match token.token_type() {
+            TokenType::Eof => None,
+            _ => Some(token.lexeme().to_string()),
+        };
+
+        ParseError::new(code, token.line(), token.span(), lexeme, message.to_string())
+    }
+}
@@ -0,0 +1,43 @@
+use super::{AstToken, Expr};
+
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDecl {
+    pub name: AstToken,
+    pub params: Vec<AstToken>,
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    Expression(Expr),
+    Print(Expr),
+    Var {
+        name: AstToken,
+        initializer: Option<Expr>,
+    },
+    Block(Vec<Stmt>),
+    // Shared so that every closure created from it points at one body.
+    Function(Rc<FunctionDecl>),
+    Class {
+        name: AstToken,
+        // Always an `Expr::Variable`, so the resolver can bind it.
+        superclass: Option<Expr>,
+        methods: Vec<Rc<FunctionDecl>>,
+    },
+    If {
+        condition: Expr,
+        then_branch: Box<Stmt>,
+        else_branch: Option<Box<Stmt>>,
+    },
+    // `for` loops are desugared into a `While` by the parser.
+    While {
+        condition: Expr,
+        body: Box<Stmt>,
+    },
+    Return {
+        keyword: AstToken,
+        value: Option<Expr>,
+    },
+}
@@ -0,0 +1,98 @@
+use crate::tokenize::KEYWORDS;
+use super::COMMANDS;
+
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+
+use unicode_ident::is_xid_continue;
+
+/// Line editor support for the REPL: completes meta-commands at the start
+/// of a line, and Lox keywords and global variables elsewhere.
+#[derive(Debug, Default)]
+pub struct LoxHelper {
+    globals: Vec<String>,
+}
+
+impl LoxHelper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the global variable names offered as completions.
+    pub fn set_globals(&mut self, globals: Vec<String>) {
+        self.globals = globals;
+    }
+
+    /// Where the word before `pos` in `line` starts, and what it may
+    /// complete to.
+    pub fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let before = &line[..pos];
+
+        if before.starts_with(':') && !before.contains(char::is_whitespace) {
+            let commands = COMMANDS
+                .iter()
+                .map(|(command, _)| *command)
+                .filter(|command| command.starts_with(before))
+                .map(|command| command.to_string())
+                .collect();
+
+            return (0, commands);
+        }
+
+        let start = before
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_xid_continue(*c))
+            .last()
+            .map_or(pos, |(index, _)| index);
+        let word = &before[start..];
+        if word.is_empty() {
+            return (pos, Vec::new());
+        }
+
+        let mut names = KEYWORDS
+            .iter()
+            .map(|(keyword, _)| *keyword)
+            .chain(self.globals.iter().map(|global| global.as_str()))
+            .filter(|name| name.starts_with(word))
+            .map(|name| name.to_string())
+            .collect::<Vec<String>>();
+        names.sort();
+        names.dedup();
+
+        (start, names)
+    }
+}
+
+impl Completer for LoxHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.candidates(line, pos))
+    }
+}
+
+impl Hinter for LoxHelper {
+    type Hint = String;
+}
+
+impl Highlighter for LoxHelper {}
+
+impl Validator for LoxHelper {}
+
+impl Helper for LoxHelper {}
+
+#[test]
+fn test_helper_candidates() {
+    let mut helper = LoxHelper::new();
+    helper.set_globals(vec!["clock".to_string(), "counter".to_string(), "value".to_string()]);
+
+    assert_eq!(helper.candidates("c", 1), (0, vec!["class".to_string(), "clock".to_string(), "counter".to_string()]));
+    assert_eq!(helper.candidates("print va", 8), (6, vec!["value".to_string(), "var".to_string()]));
+    assert_eq!(helper.candidates("f(x) ", 5), (5, Vec::new()));
+    assert_eq!(helper.candidates(":re", 3), (0, vec![":reset".to_string()]));
+    assert_eq!(helper.candidates(":load cl", 8), (6, vec!["class".to_string(), "clock".to_string()]));
+}
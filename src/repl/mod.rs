@@ -0,0 +1,124 @@
+mod reply;
+pub use reply::Reply;
+
+mod session_error;
+pub use session_error::SessionError;
+
+mod session;
+pub use session::{Session, COMMANDS};
+
+mod helper;
+pub use helper::LoxHelper;
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[cfg(test)]
+use std::fs;
+
+#[cfg(test)]
+use crate::interpret::SharedOutput;
+
+#[cfg(test)]
+use std::io::Write;
+
+/// Feeds `lines` to a new session, returning what it printed and showed,
+/// with `...` for each incomplete input and errors prefixed by `!`. Stops
+/// at `:quit`.
+#[cfg(test)]
+fn session_transcript(lines: &[&str]) -> String {
+    let output = SharedOutput::new();
+    let mut session = Session::with_output(Box::new(output.clone()));
+
+    for line in lines {
+        let reply = session.feed(line);
+        let mut printed = output.clone();
+
+        match reply {
+            Reply::Incomplete => writeln!(printed, "...").unwrap(),
+            Reply::Done(None) => {}
+            Reply::Done(Some(value)) => writeln!(printed, "{}", value).unwrap(),
+            Reply::Errors(errors) => {
+                for error in errors {
+                    writeln!(printed, "!{}", error).unwrap();
+                }
+            }
+            Reply::Text(text) => writeln!(printed, "{}", text).unwrap(),
+            Reply::Quit => break,
+        }
+    }
+
+    output.contents()
+}
+
+#[test]
+fn test_session_keeps_globals() {
+    assert_eq!(
+        session_transcript(&["var a = 1;", "fun inc() { a = a + 1; }", "inc(); inc();", "print a;"]),
+        "3\n"
+    );
+}
+
+#[test]
+fn test_session_shows_bare_expressions() {
+    assert_eq!(session_transcript(&["1 + 2", "var a = \"x\"; a + a", "print 1", "a;", ""]), "3\nxx\n1\n");
+}
+
+#[test]
+fn test_session_continues_unbalanced_input() {
+    assert_eq!(
+        session_transcript(&["fun add(a,", "b) {", "  return a + b;", "}", "add(1, 2)"]),
+        "...\n...\n...\n3\n"
+    );
+    // Unbalanced the other way is a syntax error, not more input.
+    assert_eq!(session_transcript(&["print 1);"]), "![line 1] Error at ')': Expect ';' after value.\n");
+}
+
+#[test]
+fn test_session_survives_errors() {
+    assert_eq!(
+        session_transcript(&["print @;", "print 1 +;", "print x;", "return 1;", "{ var b = b; }", "print \"ok\";"]),
+        "![line 1] Error: Unexpected character: @\n\
+         ![line 1] Error at ';': Expect expression.\n\
+         !Undefined variable 'x'.\n[line 1]\n\
+         ![line 1] Error at 'return': Can't return from top-level code.\n\
+         ![line 1] Error at 'b': Can't read local variable in its own initializer.\n\
+         ok\n"
+    );
+}
+
+#[test]
+fn test_session_meta_commands() {
+    assert_eq!(
+        session_transcript(&[":tokens var x = 1;", ":ast (1 + 2) * -3", ":ast 1 +", ":tokens @"]),
+        "VAR var null\nIDENTIFIER x null\nEQUAL = null\nNUMBER 1 1.0\nSEMICOLON ; null\nEOF  null\n\
+         (* (group (+ 1.0 2.0)) (- 3.0))\n\
+         ![line 1] Error at end: Expect expression.\n\
+         ![line 1] Error: Unexpected character: @\n"
+    );
+    assert_eq!(
+        session_transcript(&["var b = \"two\";", "var a = 1;", ":env", ":reset", ":env", "print a;"]),
+        "a = 1\nb = two\nclock = <native fn>\nclock = <native fn>\n!Undefined variable 'a'.\n[line 1]\n"
+    );
+    assert_eq!(
+        session_transcript(&[":bogus", ":quit", "print 1;"]),
+        "!Unknown command :bogus, see :help.\n"
+    );
+    // Meta-commands aren't recognized in the middle of an input.
+    assert_eq!(session_transcript(&["print (", ":quit", ");"]), "...\n...\n![line 2] Error: Unexpected character: :\n");
+}
+
+#[test]
+fn test_session_loads_files() {
+    let path = std::env::temp_dir().join(format!("session_load_{}.lox", std::process::id()));
+    fs::write(&path, "fun square(x) {\n  return x * x;\n}\nprint square(3);\n").unwrap();
+
+    let load = format!(":load {}", path.display());
+    assert_eq!(session_transcript(&[&load, "square(4)"]), "9\n16\n");
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        session_transcript(&[&load]),
+        format!("!Failed to read file {}\n", path.display())
+    );
+}
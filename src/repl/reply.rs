@@ -0,0 +1,20 @@
+use crate::interpret::Value;
+use super::SessionError;
+
+/// What a `Session` makes of a line of input.
+#[derive(Debug)]
+pub enum Reply {
+    /// A brace or parenthesis is still open, the input goes on in the next
+    /// line.
+    Incomplete,
+    /// The input ran. A bare expression, one without a trailing `;`,
+    /// evaluates to a value to show.
+    Done(Option<Value>),
+    /// The errors the input raised at any stage. The session goes on all
+    /// the same.
+    Errors(Vec<SessionError>),
+    /// The output of a meta-command.
+    Text(String),
+    /// `:quit` was entered.
+    Quit,
+}
@@ -0,0 +1,220 @@
+use crate::diagnostics::Diagnostic;
+use crate::interpret::Interpreter;
+use crate::parse::{parse, parse_expression, Stmt};
+use crate::resolve::resolve;
+use crate::tokenize::{tokenize, Token, TokenType};
+use super::{Reply, SessionError};
+
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::rc::Rc;
+
+// The file name diagnostics about typed input refer to.
+const REPL_FILE_NAME: &str = "<repl>";
+
+/// The meta-commands a line starting with `:` may run, with their usage.
+pub const COMMANDS: [(&str, &str); 7] = [
+    (":help", ":help              list the meta-commands"),
+    (":tokens", ":tokens <source>   show the tokens of <source>"),
+    (":ast", ":ast <expr>        show the syntax tree of <expr>"),
+    (":env", ":env               list the global variables"),
+    (":load", ":load <file>       run <file> in this session"),
+    (":reset", ":reset             forget every global variable"),
+    (":quit", ":quit              end the session"),
+];
+
+/// An interactive session on the tree-walking interpreter. Input is fed a
+/// line at a time, and globals defined by one line are seen by the next.
+/// Lines starting with `:` are meta-commands, see `COMMANDS`.
+pub struct Session {
+    interpreter: Interpreter,
+    // The lines of an input still incomplete.
+    pending: String,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::from_interpreter(Interpreter::new())
+    }
+
+    /// A session whose `print` statements write to `output`.
+    pub fn with_output(output: Box<dyn Write>) -> Self {
+        Self::from_interpreter(Interpreter::with_output(output))
+    }
+
+    fn from_interpreter(interpreter: Interpreter) -> Self {
+        Self {
+            interpreter,
+            pending: String::new(),
+        }
+    }
+
+    /// The names of the global variables, sorted.
+    pub fn global_names(&self) -> Vec<String> {
+        self.interpreter.globals().into_iter().map(|(name, _)| name).collect()
+    }
+
+    /// Drops the lines of an incomplete input.
+    pub fn cancel(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Whether the last line fed left the input incomplete.
+    pub fn is_continuing(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Runs `line`, or buffers it until the braces and parentheses opened
+    /// so far are closed.
+    pub fn feed(&mut self, line: &str) -> Reply {
+        if !self.is_continuing() && line.trim_start().starts_with(':') {
+            return self.command(line.trim());
+        }
+
+        self.pending.push_str(line);
+        self.pending.push('\n');
+
+        let (tokens, _) = tokenize(&self.pending);
+        if is_unbalanced(&tokens) {
+            return Reply::Incomplete;
+        }
+
+        let source = std::mem::take(&mut self.pending);
+        self.run(REPL_FILE_NAME, &source)
+    }
+
+    fn command(&mut self, line: &str) -> Reply {
+        let (command, argument) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let argument = argument.trim();
+
+        match command {
+            ":help" => Reply::Text(COMMANDS.iter().map(|(_, usage)| *usage).collect::<Vec<&str>>().join("\n")),
+            ":tokens" => {
+                let (tokens, token_errors) = tokenize(argument);
+                if !token_errors.is_empty() {
+                    return errors(REPL_FILE_NAME, argument, &token_errors);
+                }
+
+                Reply::Text(tokens.iter().map(|token| token.to_string()).collect::<Vec<String>>().join("\n"))
+            }
+            ":ast" => {
+                let (tokens, token_errors) = tokenize(argument);
+                if !token_errors.is_empty() {
+                    return errors(REPL_FILE_NAME, argument, &token_errors);
+                }
+
+                match parse_expression(tokens) {
+                    Ok(expr) => Reply::Text(expr.to_string()),
+                    Err(parse_error) => errors(REPL_FILE_NAME, argument, &[parse_error]),
+                }
+            }
+            ":env" => Reply::Text(
+                self.interpreter
+                    .globals()
+                    .iter()
+                    .map(|(name, value)| format!("{} = {}", name, value))
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+            ),
+            ":load" => match fs::read_to_string(argument) {
+                Ok(source) => self.run(argument, &source),
+                Err(_) => Reply::Errors(vec![SessionError::Command(format!("Failed to read file {}", argument))]),
+            },
+            ":reset" => {
+                self.interpreter.reset();
+                Reply::Done(None)
+            }
+            ":quit" => Reply::Quit,
+            _ => Reply::Errors(vec![SessionError::Command(format!("Unknown command {}, see :help.", command))]),
+        }
+    }
+
+    /// Runs the complete input `source`, read from `file_name`.
+    fn run(&mut self, file_name: &str, source: &str) -> Reply {
+        let (mut tokens, token_errors) = tokenize(source);
+
+        if !token_errors.is_empty() {
+            return errors(file_name, source, &token_errors);
+        }
+
+        // Finish a bare expression with the `;` it lacks, and show its
+        // value once it ran.
+        let eof = tokens.pop().expect("the token stream ends with `Eof`");
+        let is_bare = match tokens.last() {
+            None => return Reply::Done(None),
+            Some(token) => !matches!(token.token_type(), TokenType::Semicolon | TokenType::RightBrace),
+        };
+        if is_bare {
+            let semicolon = Token::new(TokenType::Semicolon, ";", None, eof.line());
+            tokens.push(match eof.span() {
+                Some(span) => semicolon.with_span(span),
+                None => semicolon,
+            });
+        }
+        tokens.push(eof);
+
+        let mut statements = match parse(tokens) {
+            Ok(statements) => statements,
+            Err(parse_errors) => return errors(file_name, source, &parse_errors),
+        };
+
+        match resolve(&statements) {
+            Ok(locals) => self.interpreter.resolve(locals),
+            Err(resolve_errors) => return errors(file_name, source, &resolve_errors),
+        }
+
+        let shown = match statements.last() {
+            Some(Stmt::Expression(_)) if is_bare => statements.pop(),
+            _ => None,
+        };
+
+        let result = self.interpreter.interpret(&statements).and_then(|()| match shown {
+            Some(Stmt::Expression(expr)) => self.interpreter.evaluate(&expr).map(Some),
+            _ => Ok(None),
+        });
+
+        match result {
+            Ok(value) => Reply::Done(value),
+            Err(runtime_error) => errors(file_name, source, &[runtime_error]),
+        }
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The reply for `errors`, found in `source` read from `file_name`.
+fn errors<'e, E>(file_name: &str, source: &str, errors: &'e [E]) -> Reply
+where
+    E: fmt::Display,
+    Diagnostic: From<&'e E>,
+{
+    let source: Rc<str> = source.into();
+
+    Reply::Errors(
+        errors
+            .iter()
+            .map(|error| SessionError::Source {
+                text: error.to_string(),
+                diagnostic: Box::new(error.into()),
+                file_name: file_name.to_string(),
+                source: source.clone(),
+            })
+            .collect(),
+    )
+}
+
+/// Whether `tokens` open more braces or parentheses than they close.
+fn is_unbalanced(tokens: &[Token<'_>]) -> bool {
+    let depth = tokens.iter().fold(0, |depth, token| match token.token_type() {
+        TokenType::LeftParen | TokenType::LeftBrace => depth + 1,
+        TokenType::RightParen | TokenType::RightBrace => depth - 1,
+        _ => depth,
+    });
+
+    depth > 0
+}
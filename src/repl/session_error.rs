@@ -0,0 +1,29 @@
+use crate::diagnostics::Diagnostic;
+
+use std::fmt;
+use std::rc::Rc;
+
+/// An error a `Session` reports.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionError {
+    /// An error in Lox source: its plain text, as the `run` command prints
+    /// it, and its diagnostic. `source` is the text the diagnostic's span
+    /// refers to, read from `file_name`, which is `<repl>` for typed input.
+    Source {
+        text: String,
+        diagnostic: Box<Diagnostic>,
+        file_name: String,
+        source: Rc<str>,
+    },
+    /// A meta-command that couldn't run, which has no source to point at.
+    Command(String),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Source { text, .. } => write!(f, "{}", text),
+            SessionError::Command(text) => write!(f, "{}", text),
+        }
+    }
+}
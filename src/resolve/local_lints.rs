@@ -0,0 +1,123 @@
+use crate::diagnostics::Code;
+use crate::tokenize::Span;
+use super::ResolveWarning;
+
+#[derive(Debug)]
+struct Local {
+    name: String,
+    line: u32,
+    span: Option<Span>,
+    used: bool,
+    // Only `var`s are warned about when unused.
+    warn_if_unused: bool,
+}
+
+/// The warnings and notes about local variables, found by following the scopes a
+/// pass walks through. The `Resolver` and the bytecode `Compiler` both
+/// drive one, so that both backends warn alike.
+///
+/// Globals and implicit names like `this` are never declared here, and
+/// uses of them are ignored.
+#[derive(Debug, Default)]
+pub(crate) struct LocalLints {
+    scopes: Vec<Vec<Local>>,
+    warnings: Vec<ResolveWarning>,
+}
+
+impl LocalLints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn begin_scope(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    /// Leaves the innermost scope, warning about its unused variables.
+    pub fn end_scope(&mut self) {
+        let Some(scope) = self.scopes.pop() else {
+            return;
+        };
+
+        for local in scope.into_iter().filter(|local| local.warn_if_unused && !local.used) {
+            self.warnings.push(ResolveWarning::new(
+                &Code::UNUSED_VARIABLE,
+                local.line,
+                local.span,
+                format!("Local variable '{}' is never used.", local.name),
+            ));
+        }
+    }
+
+    /// The number of scopes entered and not left yet.
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Forgets the scopes entered since `depth`, without warning about
+    /// them, as when a declaration is abandoned after a syntax error.
+    pub fn truncate(&mut self, depth: usize) {
+        self.scopes.truncate(depth);
+    }
+
+    /// Declares a local variable in the innermost scope, if there is one,
+    /// noting when it shadows a local of an enclosing scope.
+    pub fn declare(&mut self, name: &str, line: u32, span: Option<Span>) {
+        let Some((scope, enclosing)) = self.scopes.split_last_mut() else {
+            return;
+        };
+
+        let shadowed = enclosing
+            .iter()
+            .rev()
+            .find_map(|scope| scope.iter().rev().find(|local| local.name == name));
+
+        if let Some(shadowed) = shadowed {
+            self.warnings.push(
+                ResolveWarning::new(
+                    &Code::SHADOWED_VARIABLE,
+                    line,
+                    span,
+                    format!("Local variable '{}' shadows a variable of an enclosing scope.", name),
+                )
+                .with_shadowed_declaration(shadowed.span),
+            );
+        }
+
+        scope.push(Local {
+            name: name.to_string(),
+            line,
+            span,
+            used: false,
+            warn_if_unused: false,
+        });
+    }
+
+    /// Marks the variable just declared, a `var`, to be warned about if
+    /// it's never used, unless its name starts with `_`.
+    pub fn warn_if_unused(&mut self) {
+        if let Some(local) = self.scopes.last_mut().and_then(|scope| scope.last_mut()) {
+            local.warn_if_unused = !local.name.starts_with('_');
+        }
+    }
+
+    /// Marks the innermost variable called `name` as used, by a read or
+    /// an assignment.
+    pub fn mark_used(&mut self, name: &str) {
+        let local = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.iter_mut().rev().find(|local| local.name == name));
+
+        if let Some(local) = local {
+            local.used = true;
+        }
+    }
+
+    /// The warnings found, in source order.
+    pub fn into_warnings(mut self) -> Vec<ResolveWarning> {
+        self.warnings.sort_by_key(|warning| (warning.line(), warning.span().map(|span| span.start().offset())));
+        self.warnings
+    }
+}
@@ -0,0 +1,139 @@
+mod resolve_error;
+pub use resolve_error::ResolveError;
+
+mod resolve_warning;
+pub use resolve_warning::ResolveWarning;
+
+mod resolver;
+pub use resolver::Resolver;
+
+mod local_lints;
+pub(crate) use local_lints::LocalLints;
+
+use crate::parse::{ExprId, Stmt};
+
+use std::collections::HashMap;
+
+/// For each resolved local variable reference, the number of scopes
+/// between the reference and the scope that declares it.
+pub type Locals = HashMap<ExprId, usize>;
+
+/// Resolves every variable reference in `statements`, or returns all the
+/// static errors found.
+pub fn resolve(statements: &[Stmt]) -> Result<Locals, Vec<ResolveError>> {
+    resolve_with_warnings(statements).map(|(locals, _)| locals)
+}
+
+/// Resolves like `resolve`, also returning the warnings found in a program
+/// without errors.
+pub fn resolve_with_warnings(statements: &[Stmt]) -> Result<(Locals, Vec<ResolveWarning>), Vec<ResolveError>> {
+    Resolver::new().resolve(statements)
+}
+
+#[cfg(test)]
+use crate::parse::parse;
+#[cfg(test)]
+use crate::tokenize::tokenize;
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[cfg(test)]
+fn resolve_errors(file_contents: &str) -> Vec<String> {
+    let (tokens, _) = tokenize(file_contents);
+    let statements = parse(tokens).unwrap();
+
+    match resolve(&statements) {
+        Ok(_) => Vec::new(),
+        Err(resolve_errors) => resolve_errors
+            .iter()
+            .map(|resolve_error| resolve_error.to_string())
+            .collect(),
+    }
+}
+
+#[test]
+fn test_resolve_local_depths() {
+    let (tokens, _) = tokenize("var g; { var a; { a; g; } }");
+    let statements = parse(tokens).unwrap();
+    let locals = resolve(&statements).unwrap();
+
+    let mut depths = locals.values().copied().collect::<Vec<usize>>();
+    depths.sort();
+
+    // Only `a` is local, one scope up; the global `g` gets no entry.
+    assert_eq!(depths, vec![1]);
+}
+
+#[test]
+fn test_resolve_errors() {
+    assert_eq!(
+        resolve_errors("{ var a = a; }"),
+        vec!["[line 1] Error at 'a': Can't read local variable in its own initializer."]
+    );
+    assert_eq!(
+        resolve_errors("fun f() { var a; var a; }"),
+        vec!["[line 1] Error at 'a': Already a variable with this name in this scope."]
+    );
+    assert_eq!(
+        resolve_errors("return 1;"),
+        vec!["[line 1] Error at 'return': Can't return from top-level code."]
+    );
+    assert_eq!(
+        resolve_errors("print this;\nsuper.m();"),
+        vec![
+            "[line 1] Error at 'this': Can't use 'this' outside of a class.",
+            "[line 2] Error at 'super': Can't use 'super' outside of a class.",
+        ]
+    );
+}
+
+#[test]
+fn test_resolve_class_errors() {
+    assert_eq!(
+        resolve_errors("class A < A {}"),
+        vec!["[line 1] Error at 'A': A class can't inherit from itself."]
+    );
+    assert_eq!(
+        resolve_errors("class A { init() { return 1; } }"),
+        vec!["[line 1] Error at 'return': Can't return a value from an initializer."]
+    );
+    assert_eq!(
+        resolve_errors("class A { m() { super.m(); } }"),
+        vec!["[line 1] Error at 'super': Can't use 'super' in a class with no superclass."]
+    );
+    assert!(resolve_errors("class A { init() { return; } m() { return this; } }").is_empty());
+}
+
+#[test]
+fn test_resolve_allows_global_redeclaration() {
+    assert!(resolve_errors("var a = 1; var a = a;").is_empty());
+}
+
+#[test]
+fn test_resolve_warnings() {
+    let source = "\
+fun f(unused_parameter) {
+  var unused;
+  var _kept;
+  var read = 1;
+  var assigned;
+  assigned = read;
+  fun g() { var closed = 1; fun h() { return closed; } return h; }
+}
+{
+  var shadowed = 1;
+  { var shadowed = 2; print shadowed; }
+}";
+    let (tokens, _) = tokenize(source);
+    let (_, warnings) = resolve_with_warnings(&parse(tokens).unwrap()).unwrap();
+
+    assert_eq!(
+        warnings.iter().map(|warning| (warning.line(), warning.message())).collect::<Vec<(u32, &str)>>(),
+        vec![
+            (2, "Local variable 'unused' is never used."),
+            (10, "Local variable 'shadowed' is never used."),
+            (11, "Local variable 'shadowed' shadows a variable of an enclosing scope."),
+        ]
+    );
+}
@@ -0,0 +1,70 @@
+use crate::diagnostics::Code;
+use crate::tokenize::Span;
+
+use std::fmt;
+
+/// A static error found by the resolver, rendered like a `ParseError`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolveError {
+    code: &'static Code,
+    line: u32,
+    span: Option<Span>,
+    lexeme: String,
+    message: String,
+    // For a name declared twice in a scope, where it was declared first.
+    first_declaration: Option<Span>,
+}
+
+impl ResolveError {
+    pub fn new(code: &'static Code, line: u32, span: Option<Span>, lexeme: String, message: String) -> Self {
+        Self {
+            code,
+            line,
+            span,
+            lexeme,
+            message,
+            first_declaration: None,
+        }
+    }
+
+    pub fn with_first_declaration(mut self, span: Option<Span>) -> Self {
+        self.first_declaration = span;
+        self
+    }
+
+    pub fn code(&self) -> &'static Code {
+        self.code
+    }
+
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    pub fn lexeme(&self) -> &str {
+        &self.lexeme
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn first_declaration(&self) -> Option<Span> {
+        self.first_declaration
+    }
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[line {}] Error at '{}': {}",
+            self.line,
+            self.lexeme,
+            self.message
+        )
+    }
+}
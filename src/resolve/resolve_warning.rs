@@ -0,0 +1,53 @@
+use crate::diagnostics::Code;
+use crate::tokenize::Span;
+
+/// A problem found by the resolver that doesn't stop the program from
+/// running, reported with the severity of its code. Unlike errors it has
+/// no plain text form, since plain output has no warnings: it is only
+/// shown as a `Diagnostic`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolveWarning {
+    code: &'static Code,
+    line: u32,
+    span: Option<Span>,
+    message: String,
+    // For a variable shadowing another, where the other was declared.
+    shadowed_declaration: Option<Span>,
+}
+
+impl ResolveWarning {
+    pub fn new(code: &'static Code, line: u32, span: Option<Span>, message: String) -> Self {
+        Self {
+            code,
+            line,
+            span,
+            message,
+            shadowed_declaration: None,
+        }
+    }
+
+    pub fn with_shadowed_declaration(mut self, span: Option<Span>) -> Self {
+        self.shadowed_declaration = span;
+        self
+    }
+
+    pub fn code(&self) -> &'static Code {
+        self.code
+    }
+
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn shadowed_declaration(&self) -> Option<Span> {
+        self.shadowed_declaration
+    }
+}
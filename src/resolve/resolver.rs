@@ -0,0 +1,330 @@
+use crate::diagnostics::Code;
+use crate::parse::{AstToken, Expr, ExprId, FunctionDecl, Stmt};
+use super::{LocalLints, Locals, ResolveError, ResolveWarning};
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FunctionType {
+    None,
+    Function,
+    Method,
+    // A class's `init` method, which can't return a value.
+    Initializer,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
+#[derive(Debug)]
+struct Variable {
+    // Whether its initializer has been resolved.
+    defined: bool,
+    // `None` for the implicit `this` and `super`.
+    declaration: Option<AstToken>,
+}
+
+/// Static pass between parsing and interpretation.
+///
+/// It walks the tree once, tracking the block scopes, and records for
+/// every local variable reference how many scopes separate it from its
+/// declaration. References that are not found in any scope are globals and
+/// get no entry. Along the way it warns about local variables that are
+/// never used.
+#[derive(Debug)]
+pub struct Resolver {
+    scopes: Vec<HashMap<String, Variable>>,
+    locals: Locals,
+    errors: Vec<ResolveError>,
+    lints: LocalLints,
+
+    current_function: FunctionType,
+    current_class: ClassType,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Self {
+            scopes: Vec::new(),
+            locals: HashMap::new(),
+            errors: Vec::new(),
+            lints: LocalLints::new(),
+
+            current_function: FunctionType::None,
+            current_class: ClassType::None,
+        }
+    }
+
+    /// The local references and the warnings, in source order, or the
+    /// errors.
+    pub fn resolve(mut self, statements: &[Stmt]) -> Result<(Locals, Vec<ResolveWarning>), Vec<ResolveError>> {
+        self.resolve_statements(statements);
+
+        if !self.errors.is_empty() {
+            return Err(self.errors);
+        }
+
+        Ok((self.locals, self.lints.into_warnings()))
+    }
+
+    fn resolve_statements(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            self.resolve_statement(statement);
+        }
+    }
+
+    fn resolve_statement(&mut self, statement: &Stmt) {
+        match statement {
+            Stmt::Expression(expr) | Stmt::Print(expr) => self.resolve_expression(expr),
+            Stmt::Var { name, initializer } => {
+                self.declare(name);
+                self.lints.warn_if_unused();
+                if let Some(initializer) = initializer {
+                    self.resolve_expression(initializer);
+                }
+                self.define(name);
+            }
+            Stmt::Block(statements) => {
+                self.begin_scope();
+                self.resolve_statements(statements);
+                self.end_scope();
+            }
+            Stmt::Function(declaration) => {
+                // Defined before the body is resolved, so it can recurse.
+                self.declare(&declaration.name);
+                self.define(&declaration.name);
+
+                self.resolve_function(declaration, FunctionType::Function);
+            }
+            Stmt::Class { name, superclass, methods } => {
+                let enclosing_class = std::mem::replace(&mut self.current_class, ClassType::Class);
+
+                self.declare(name);
+                self.define(name);
+
+                if let Some(superclass) = superclass {
+                    if let Expr::Variable { name: superclass_name, .. } = superclass {
+                        if superclass_name.lexeme() == name.lexeme() {
+                            self.error(&Code::SELF_INHERITANCE, superclass_name, "A class can't inherit from itself.");
+                        }
+                    }
+
+                    self.current_class = ClassType::Subclass;
+                    self.resolve_expression(superclass);
+
+                    // Methods close over a scope holding `super`...
+                    self.begin_scope();
+                    self.define_name("super");
+                }
+
+                // ...inside which each method's `this` is bound.
+                self.begin_scope();
+                self.define_name("this");
+
+                for method in methods {
+                    let function_type = match method.name.lexeme() {
+                        "init" => FunctionType::Initializer,
+                        _ => FunctionType::Method,
+                    };
+                    self.resolve_function(method, function_type);
+                }
+
+                self.end_scope();
+                if superclass.is_some() {
+                    self.end_scope();
+                }
+
+                self.current_class = enclosing_class;
+            }
+            Stmt::If { condition, then_branch, else_branch } => {
+                self.resolve_expression(condition);
+                self.resolve_statement(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.resolve_statement(else_branch);
+                }
+            }
+            Stmt::While { condition, body } => {
+                self.resolve_expression(condition);
+                self.resolve_statement(body);
+            }
+            Stmt::Return { keyword, value } => {
+                if self.current_function == FunctionType::None {
+                    self.error(&Code::TOP_LEVEL_RETURN, keyword, "Can't return from top-level code.");
+                }
+
+                if let Some(value) = value {
+                    if self.current_function == FunctionType::Initializer {
+                        self.error(&Code::INITIALIZER_RETURN_VALUE, keyword, "Can't return a value from an initializer.");
+                    }
+
+                    self.resolve_expression(value);
+                }
+            }
+        }
+    }
+
+    fn resolve_function(&mut self, declaration: &FunctionDecl, function_type: FunctionType) {
+        let enclosing_function = std::mem::replace(&mut self.current_function, function_type);
+
+        self.begin_scope();
+        for param in &declaration.params {
+            self.declare(param);
+            self.define(param);
+        }
+        self.resolve_statements(&declaration.body);
+        self.end_scope();
+
+        self.current_function = enclosing_function;
+    }
+
+    fn resolve_expression(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(_) => {}
+            Expr::Grouping(expression) => self.resolve_expression(expression),
+            Expr::Unary { right, .. } => self.resolve_expression(right),
+            Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+                self.resolve_expression(left);
+                self.resolve_expression(right);
+            }
+            Expr::Variable { id, name } => {
+                let declared_but_undefined = self
+                    .scopes
+                    .last()
+                    .and_then(|scope| scope.get(name.lexeme()))
+                    .is_some_and(|variable| !variable.defined);
+
+                if declared_but_undefined {
+                    self.error(&Code::SELF_REFERENCING_INITIALIZER, name, "Can't read local variable in its own initializer.");
+                }
+
+                self.resolve_local(*id, name);
+            }
+            Expr::Assign { id, name, value } => {
+                self.resolve_expression(value);
+                self.resolve_local(*id, name);
+            }
+            Expr::Call { callee, arguments, .. } => {
+                self.resolve_expression(callee);
+                for argument in arguments {
+                    self.resolve_expression(argument);
+                }
+            }
+            Expr::Get { object, .. } => self.resolve_expression(object),
+            Expr::Set { object, value, .. } => {
+                self.resolve_expression(value);
+                self.resolve_expression(object);
+            }
+            Expr::This { id, keyword } => {
+                if self.current_class == ClassType::None {
+                    self.error(&Code::THIS_OUTSIDE_CLASS, keyword, "Can't use 'this' outside of a class.");
+                    return;
+                }
+
+                self.resolve_local(*id, keyword);
+            }
+            Expr::Super { id, keyword, .. } => {
+                match self.current_class {
+                    ClassType::None => {
+                        self.error(&Code::SUPER_OUTSIDE_CLASS, keyword, "Can't use 'super' outside of a class.");
+                        return;
+                    }
+                    ClassType::Class => {
+                        self.error(&Code::SUPER_WITHOUT_SUPERCLASS, keyword, "Can't use 'super' in a class with no superclass.");
+                        return;
+                    }
+                    ClassType::Subclass => {}
+                }
+
+                self.resolve_local(*id, keyword);
+            }
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+        self.lints.begin_scope();
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+        self.lints.end_scope();
+    }
+
+    fn declare(&mut self, name: &AstToken) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+
+        let variable = Variable {
+            defined: false,
+            declaration: Some(name.clone()),
+        };
+        let first_declaration = scope
+            .insert(name.lexeme().to_string(), variable)
+            .and_then(|previous| previous.declaration);
+
+        if let Some(first_declaration) = first_declaration {
+            let message = "Already a variable with this name in this scope.";
+            self.errors.push(
+                Self::resolve_error(&Code::DUPLICATE_DECLARATION, name, message)
+                    .with_first_declaration(first_declaration.span())
+            );
+        }
+
+        self.lints.declare(name.lexeme(), name.line(), name.span());
+    }
+
+    fn define(&mut self, name: &AstToken) {
+        if let Some(variable) = self.scopes.last_mut().and_then(|scope| scope.get_mut(name.lexeme())) {
+            variable.defined = true;
+        }
+    }
+
+    /// Defines an implicit name, such as `this`, in the innermost scope.
+    fn define_name(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            let variable = Variable {
+                defined: true,
+                declaration: None,
+            };
+            scope.insert(name.to_string(), variable);
+        }
+    }
+
+    fn resolve_local(&mut self, id: ExprId, name: &AstToken) {
+        let depth = self
+            .scopes
+            .iter()
+            .rev()
+            .position(|scope| scope.contains_key(name.lexeme()));
+
+        if let Some(depth) = depth {
+            self.locals.insert(id, depth);
+        }
+        self.lints.mark_used(name.lexeme());
+    }
+
+    fn error(&mut self, code: &'static Code, token: &AstToken, message: &str) {
+        self.errors.push(Self::resolve_error(code, token, message));
+    }
+
+    fn resolve_error(code: &'static Code, token: &AstToken, message: &str) -> ResolveError {
+        ResolveError::new(
+            code,
+            token.line(),
+            token.span(),
+            token.lexeme().to_string(),
+            message.to_string()
+        )
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
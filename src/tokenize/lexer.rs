@@ -0,0 +1,110 @@
+use crate::tokenize::{Token, TokenizerError};
+use super::scanner::Scanner;
+
+use std::iter::FusedIterator;
+
+/// Lazily scans `source` one token at a time.
+///
+/// Every item is either a token or the error found while scanning it, and
+/// the stream always ends with a single `Eof` token. Each error is followed
+/// by an `Error` token covering the offending text, so consumers that only
+/// look at tokens can still resynchronise. Lexemes are slices of `source`,
+/// so nothing is copied unless a literal needs it.
+#[derive(Debug, Clone)]
+pub struct Lexer<'src> {
+    scanner: Scanner<'src>,
+    finished: bool,
+}
+
+impl<'src> Lexer<'src> {
+    pub fn new(source: &'src str) -> Self {
+        Self {
+            scanner: Scanner::new(source),
+            finished: false,
+        }
+    }
+
+    /// Keeps the whitespace and comments as trivia on the tokens, so that
+    /// joining every token with its trivia gives back the original source.
+    pub fn with_trivia(mut self) -> Self {
+        self.scanner = self.scanner.with_trivia();
+        self
+    }
+}
+
+impl<'src> Iterator for Lexer<'src> {
+    type Item = Result<Token<'src>, TokenizerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(token) = self.scanner.take_error_token() {
+            return Some(Ok(token));
+        }
+
+        if self.finished {
+            return None;
+        }
+
+        match self.scanner.next_token() {
+            Some(result) => Some(result),
+            None => {
+                self.finished = true;
+                Some(Ok(self.scanner.eof_token()))
+            }
+        }
+    }
+}
+
+impl FusedIterator for Lexer<'_> {}
+
+#[test]
+fn test_lexer_is_lazy_and_fused() {
+    use crate::tokenize::TokenType;
+
+    let mut lexer = Lexer::new("1 $ x");
+
+    let token = lexer.next().unwrap().unwrap();
+    assert_eq!(token.token_type(), &TokenType::Number);
+    assert_eq!(token.lexeme(), "1");
+
+    let token_error = lexer.next().unwrap().unwrap_err();
+    assert_eq!(token_error.to_string(), "[line 1] Error: Unexpected character: $");
+
+    let token = lexer.next().unwrap().unwrap();
+    assert_eq!(token.token_type(), &TokenType::Error);
+    assert_eq!(token.lexeme(), "$");
+    assert_eq!(token.span(), token_error.span());
+
+    let token = lexer.next().unwrap().unwrap();
+    assert_eq!(token.lexeme(), "x");
+
+    let token = lexer.next().unwrap().unwrap();
+    assert_eq!(token.token_type(), &TokenType::Eof);
+
+    assert!(lexer.next().is_none());
+    assert!(lexer.next().is_none());
+}
+
+#[test]
+fn test_lexer_with_trivia() {
+    let tokens = Lexer::new("  a // one\n/* two */ b\t")
+        .with_trivia()
+        .map(|result| result.unwrap())
+        .map(|token| (token.leading_trivia(), token.lexeme(), token.trailing_trivia()))
+        .collect::<Vec<_>>();
+
+    let expected = vec![
+        ("  ", "a", " // one"),
+        ("\n/* two */ ", "b", "\t"),
+        ("", "", ""),
+    ];
+
+    assert_eq!(tokens, expected);
+}
+
+#[test]
+fn test_lexer_without_trivia() {
+    let token = Lexer::new("  a // one").next().unwrap().unwrap();
+
+    assert_eq!(token.leading_trivia(), "");
+    assert_eq!(token.trailing_trivia(), "");
+}
@@ -1,37 +1,53 @@
 mod token_type;
-use token_type::TokenType;
+pub use token_type::TokenType;
 
 mod token;
-pub use token::Token;
+pub use token::{LiteralType, Token};
 
 mod token_error;
 pub use token_error::TokenizerError;
 
+mod span;
+pub use span::{Position, Span};
+
 mod scanner;
-use scanner::Scanner;
+pub use scanner::KEYWORDS;
+
+mod lexer;
+pub use lexer::Lexer;
 
-#[allow(dead_code)]
-pub fn tokenize(file_contents: String) -> (Vec<Token>, Vec<TokenizerError>) {
-    let mut scanner = Scanner::new(file_contents);
+/// Scans the whole source eagerly, splitting the `Lexer` output into the
+/// tokens and the errors found along the way.
+pub fn tokenize(file_contents: &str) -> (Vec<Token<'_>>, Vec<TokenizerError>) {
+    let mut tokens = Vec::new();
+    let mut token_errors = Vec::new();
 
-    let tokens = scanner.scan_tokens();
-    let token_errors = scanner.token_errors();
+    for result in Lexer::new(file_contents) {
+        match result {
+            Ok(token) => tokens.push(token),
+            Err(token_error) => token_errors.push(token_error),
+        }
+    }
 
     (tokens, token_errors)
 }
 
+#[cfg(test)]
+use crate::diagnostics::Code;
+
+#[cfg(test)]
 use pretty_assertions::assert_eq;
 
 #[test]
 fn test_tokenize_parentheses() {
     let file_contents = String::from("(()");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
-        Token::new(TokenType::LeftParen, "(".to_string(), None, 1),
-        Token::new(TokenType::LeftParen, "(".to_string(), None, 1),
-        Token::new(TokenType::RightParen, ")".to_string(), None, 1),
-        Token::new(TokenType::Eof, "".to_string(), None, 1),
+        Token::new(TokenType::LeftParen, "(", None, 1),
+        Token::new(TokenType::LeftParen, "(", None, 1),
+        Token::new(TokenType::RightParen, ")", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -41,7 +57,7 @@ fn test_tokenize_parentheses() {
 #[test]
 fn test_tokenize_brackets() {
     let file_contents = String::from("{{}}");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     /*
     let tokens_msg = tokens
@@ -52,11 +68,11 @@ fn test_tokenize_brackets() {
     */
 
     let expected_tokens = vec![
-        Token::new(TokenType::LeftBrace, "{".to_string(), None, 1),
-        Token::new(TokenType::LeftBrace, "{".to_string(), None, 1),
-        Token::new(TokenType::RightBrace, "}".to_string(), None, 1),
-        Token::new(TokenType::RightBrace, "}".to_string(), None, 1),
-        Token::new(TokenType::Eof, "".to_string(), None, 1),
+        Token::new(TokenType::LeftBrace, "{", None, 1),
+        Token::new(TokenType::LeftBrace, "{", None, 1),
+        Token::new(TokenType::RightBrace, "}", None, 1),
+        Token::new(TokenType::RightBrace, "}", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -66,19 +82,19 @@ fn test_tokenize_brackets() {
 #[test]
 fn test_tokenize_single_chars() {
     let file_contents = String::from("({*.,+*})");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
-        Token::new(TokenType::LeftParen, "(".to_string(), None, 1),
-        Token::new(TokenType::LeftBrace, "{".to_string(), None, 1),
-        Token::new(TokenType::Star, "*".to_string(), None, 1),
-        Token::new(TokenType::Dot, ".".to_string(), None, 1),
-        Token::new(TokenType::Comma, ",".to_string(), None, 1),
-        Token::new(TokenType::Plus, "+".to_string(), None, 1),
-        Token::new(TokenType::Star, "*".to_string(), None, 1),
-        Token::new(TokenType::RightBrace, "}".to_string(), None, 1),
-        Token::new(TokenType::RightParen, ")".to_string(), None, 1),
-        Token::new(TokenType::Eof, "".to_string(), None, 1),
+        Token::new(TokenType::LeftParen, "(", None, 1),
+        Token::new(TokenType::LeftBrace, "{", None, 1),
+        Token::new(TokenType::Star, "*", None, 1),
+        Token::new(TokenType::Dot, ".", None, 1),
+        Token::new(TokenType::Comma, ",", None, 1),
+        Token::new(TokenType::Plus, "+", None, 1),
+        Token::new(TokenType::Star, "*", None, 1),
+        Token::new(TokenType::RightBrace, "}", None, 1),
+        Token::new(TokenType::RightParen, ")", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -88,18 +104,20 @@ fn test_tokenize_single_chars() {
 #[test]
 fn test_tokenize_single_chars_with_unexpected_chars() {
     let file_contents = String::from(",.$(#");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
-        Token::new(TokenType::Comma, ",".to_string(), None, 1),
-        Token::new(TokenType::Dot, ".".to_string(), None, 1),
-        Token::new(TokenType::LeftParen, "(".to_string(), None, 1),
-        Token::new(TokenType::Eof, "".to_string(), None, 1),
+        Token::new(TokenType::Comma, ",", None, 1),
+        Token::new(TokenType::Dot, ".", None, 1),
+        Token::new(TokenType::Error, "$", None, 1),
+        Token::new(TokenType::LeftParen, "(", None, 1),
+        Token::new(TokenType::Error, "#", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
     ];
 
     let expected_token_errors = vec![
-        TokenizerError::new(1, "Unexpected character: $".to_string()),
-        TokenizerError::new(1, "Unexpected character: #".to_string()),
+        TokenizerError::new(&Code::UNEXPECTED_CHARACTER, 1, "Unexpected character: $".to_string()),
+        TokenizerError::new(&Code::UNEXPECTED_CHARACTER, 1, "Unexpected character: #".to_string()),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -109,17 +127,19 @@ fn test_tokenize_single_chars_with_unexpected_chars() {
 #[test]
 fn test_tokenize_single_chars_with_unexpected_chars_multi_lines() {
     let file_contents = String::from("# (\n)\t@");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
-        Token::new(TokenType::LeftParen, "(".to_string(), None, 1),
-        Token::new(TokenType::RightParen, ")".to_string(), None, 2),
-        Token::new(TokenType::Eof, "".to_string(), None, 2),
+        Token::new(TokenType::Error, "#", None, 1),
+        Token::new(TokenType::LeftParen, "(", None, 1),
+        Token::new(TokenType::RightParen, ")", None, 2),
+        Token::new(TokenType::Error, "@", None, 2),
+        Token::new(TokenType::Eof, "", None, 2),
     ];
 
     let expected_token_errors = vec![
-        TokenizerError::new(1, "Unexpected character: #".to_string()),
-        TokenizerError::new(2, "Unexpected character: @".to_string()),
+        TokenizerError::new(&Code::UNEXPECTED_CHARACTER, 1, "Unexpected character: #".to_string()),
+        TokenizerError::new(&Code::UNEXPECTED_CHARACTER, 2, "Unexpected character: @".to_string()),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -129,11 +149,11 @@ fn test_tokenize_single_chars_with_unexpected_chars_multi_lines() {
 #[test]
 fn test_tokenize_operators_single_equal() {
     let file_contents = String::from("=");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
-        Token::new(TokenType::Equal, "=".to_string(), None, 1),
-        Token::new(TokenType::Eof, "".to_string(), None, 1),
+        Token::new(TokenType::Equal, "=", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -143,15 +163,15 @@ fn test_tokenize_operators_single_equal() {
 #[test]
 fn test_tokenize_operators_chars_1() {
     let file_contents = String::from("={===}");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
-        Token::new(TokenType::Equal, "=".to_string(), None, 1),
-        Token::new(TokenType::LeftBrace, "{".to_string(), None, 1),
-        Token::new(TokenType::EqualEqual, "==".to_string(), None, 1),
-        Token::new(TokenType::Equal, "=".to_string(), None, 1),
-        Token::new(TokenType::RightBrace, "}".to_string(), None, 1),
-        Token::new(TokenType::Eof, "".to_string(), None, 1),
+        Token::new(TokenType::Equal, "=", None, 1),
+        Token::new(TokenType::LeftBrace, "{", None, 1),
+        Token::new(TokenType::EqualEqual, "==", None, 1),
+        Token::new(TokenType::Equal, "=", None, 1),
+        Token::new(TokenType::RightBrace, "}", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -161,13 +181,13 @@ fn test_tokenize_operators_chars_1() {
 #[test]
 fn test_tokenize_operators_chars_2() {
     let file_contents = String::from("!!===");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
-        Token::new(TokenType::Bang, "!".to_string(), None, 1),
-        Token::new(TokenType::BangEqual, "!=".to_string(), None, 1),
-        Token::new(TokenType::EqualEqual, "==".to_string(), None, 1),
-        Token::new(TokenType::Eof, "".to_string(), None, 1),
+        Token::new(TokenType::Bang, "!", None, 1),
+        Token::new(TokenType::BangEqual, "!=", None, 1),
+        Token::new(TokenType::EqualEqual, "==", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -177,14 +197,14 @@ fn test_tokenize_operators_chars_2() {
 #[test]
 fn test_tokenize_operators_chars_3() {
     let file_contents = String::from("<<=>>=");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
-        Token::new(TokenType::Less, "<".to_string(), None, 1),
-        Token::new(TokenType::LessEqual, "<=".to_string(), None, 1),
-        Token::new(TokenType::Greater, ">".to_string(), None, 1),
-        Token::new(TokenType::GreaterEqual, ">=".to_string(), None, 1),
-        Token::new(TokenType::Eof, "".to_string(), None, 1),
+        Token::new(TokenType::Less, "<", None, 1),
+        Token::new(TokenType::LessEqual, "<=", None, 1),
+        Token::new(TokenType::Greater, ">", None, 1),
+        Token::new(TokenType::GreaterEqual, ">=", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -194,11 +214,11 @@ fn test_tokenize_operators_chars_3() {
 #[test]
 fn test_tokenize_slash() {
     let file_contents = String::from("/");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
-        Token::new(TokenType::Slash, "/".to_string(), None, 1),
-        Token::new(TokenType::Eof, "".to_string(), None, 1),
+        Token::new(TokenType::Slash, "/", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -209,10 +229,10 @@ fn test_tokenize_slash() {
 fn test_tokenize_slash_comment() {
     // let file_contents = String::from("// Comment\n");
     let file_contents = String::from("///Unicode:£§᯽☺♣/)");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
-        Token::new(TokenType::Eof, "".to_string(), None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -222,12 +242,12 @@ fn test_tokenize_slash_comment() {
 #[test]
 fn test_tokenize_whitespaces() {
     let file_contents = String::from("(\t\n)");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
-        Token::new(TokenType::LeftParen, "(".to_string(), None, 1),
-        Token::new(TokenType::RightParen, ")".to_string(), None, 2),
-        Token::new(TokenType::Eof, "".to_string(), None, 2),
+        Token::new(TokenType::LeftParen, "(", None, 1),
+        Token::new(TokenType::RightParen, ")", None, 2),
+        Token::new(TokenType::Eof, "", None, 2),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -238,16 +258,16 @@ fn test_tokenize_whitespaces() {
 #[test]
 fn test_tokenize_string() {
     let file_contents = String::from("\"Hello, World!\"");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
         Token::new(
             TokenType::String,
-            "\"Hello, World!\"".to_string(),
-            Some("Hello, World!".to_string()),
+            "\"Hello, World!\"",
+            Some("Hello, World!".into()),
             1
         ),
-        Token::new(TokenType::Eof, "".to_string(), None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -257,16 +277,16 @@ fn test_tokenize_string() {
 #[test]
 fn test_tokenize_number() {
     let file_contents = String::from("1234.1234");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
         Token::new_number(
             TokenType::Number,
-            "1234.1234".to_string(),
+            "1234.1234",
             Some(1234.1234),
             1
         ),
-        Token::new(TokenType::Eof, "".to_string(), None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -276,13 +296,13 @@ fn test_tokenize_number() {
 #[test]
 fn test_tokenize_identifiers() {
     let file_contents = String::from("foo bar _hello");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
-        Token::new(TokenType::Identifier, "foo".to_string(), None, 1),
-        Token::new(TokenType::Identifier, "bar".to_string(), None, 1),
-        Token::new(TokenType::Identifier, "_hello".to_string(), None, 1),
-        Token::new(TokenType::Eof, "".to_string(), None, 1),
+        Token::new(TokenType::Identifier, "foo", None, 1),
+        Token::new(TokenType::Identifier, "bar", None, 1),
+        Token::new(TokenType::Identifier, "_hello", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
     ];
 
     assert_eq!(tokens, expected_tokens);
@@ -292,15 +312,312 @@ fn test_tokenize_identifiers() {
 #[test]
 fn test_tokenize_keywords() {
     let file_contents = String::from("if else return");
-    let (tokens, token_errors) = tokenize(file_contents);
+    let (tokens, token_errors) = tokenize(&file_contents);
+
+    let expected_tokens = vec![
+        Token::new(TokenType::If, "if", None, 1),
+        Token::new(TokenType::Else, "else", None, 1),
+        Token::new(TokenType::Return, "return", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
+    ];
+
+    assert_eq!(tokens, expected_tokens);
+    assert_eq!(token_errors, vec![]);
+}
+
+#[test]
+fn test_tokenize_multibyte_comment_then_tokens() {
+    let file_contents = String::from("// £§☺ año\n(☺)");
+    let (tokens, token_errors) = tokenize(&file_contents);
+
+    let expected_tokens = vec![
+        Token::new(TokenType::LeftParen, "(", None, 2),
+        Token::new(TokenType::Error, "☺", None, 2),
+        Token::new(TokenType::RightParen, ")", None, 2),
+        Token::new(TokenType::Eof, "", None, 2),
+    ];
+
+    let expected_token_errors = vec![
+        TokenizerError::new(&Code::UNEXPECTED_CHARACTER, 2, "Unexpected character: ☺".to_string()),
+    ];
+
+    assert_eq!(tokens, expected_tokens);
+    assert_eq!(token_errors, expected_token_errors);
+}
+
+#[test]
+fn test_tokenize_multibyte_string() {
+    let file_contents = String::from("\"canción ☺\" == \"añ\"");
+    let (tokens, token_errors) = tokenize(&file_contents);
+
+    let expected_tokens = vec![
+        Token::new(
+            TokenType::String,
+            "\"canción ☺\"",
+            Some("canción ☺".into()),
+            1
+        ),
+        Token::new(TokenType::EqualEqual, "==", None, 1),
+        Token::new(
+            TokenType::String,
+            "\"añ\"",
+            Some("añ".into()),
+            1
+        ),
+        Token::new(TokenType::Eof, "", None, 1),
+    ];
+
+    assert_eq!(tokens, expected_tokens);
+    assert_eq!(token_errors, vec![]);
+}
+
+#[test]
+fn test_tokenize_spans() {
+    let file_contents = String::from("var ☺x\n  = \"a\nb\";");
+    let (tokens, token_errors) = tokenize(&file_contents);
+
+    let spans = tokens
+        .iter()
+        .map(|token| {
+            let span = token.span().unwrap();
+            let (start, end) = (span.start(), span.end());
+            (
+                (start.line(), start.column(), start.offset()),
+                (end.line(), end.column(), end.offset()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let expected_spans = vec![
+        ((1, 1, 0), (1, 4, 3)),   // var
+        ((1, 5, 4), (1, 6, 7)),   // ☺
+        ((1, 6, 7), (1, 7, 8)),   // x
+        ((2, 3, 11), (2, 4, 12)), // =
+        ((2, 5, 13), (3, 3, 18)), // "a\nb"
+        ((3, 3, 18), (3, 4, 19)), // ;
+        ((3, 4, 19), (3, 4, 19)), // EOF
+    ];
+
+    assert_eq!(spans, expected_spans);
+
+    let error = &token_errors[0];
+    assert_eq!(error.to_string(), "[line 1] Error: Unexpected character: ☺");
+    assert_eq!((error.line(), error.column()), (1, Some(5)));
+    assert_eq!(error.span().unwrap().start().offset(), 4);
+    assert_eq!(error.span().unwrap().end().offset(), 7);
+
+    // Tokens built without a source have no span to point at.
+    let token = Token::new(TokenType::Eof, "", None, 3);
+    assert_eq!((token.span(), token.column()), (None, None));
+}
+
+#[test]
+fn test_tokenize_string_escapes() {
+    let file_contents = String::from(r#""a\n\t\r\\\"\u{41}\u{1F600}b""#);
+    let (tokens, token_errors) = tokenize(&file_contents);
+
+    let expected_tokens = vec![
+        Token::new(
+            TokenType::String,
+            r#""a\n\t\r\\\"\u{41}\u{1F600}b""#,
+            Some("a\n\t\r\\\"A😀b".into()),
+            1
+        ),
+        Token::new(TokenType::Eof, "", None, 1),
+    ];
+
+    assert_eq!(tokens, expected_tokens);
+    assert_eq!(token_errors, vec![]);
+}
+
+#[test]
+fn test_tokenize_string_invalid_escapes() {
+    let file_contents = String::from("\"ok\\q\"\n\"a\n\\u{zz}\" \"\\u{110000}\" \"\\u41\"");
+    let (tokens, token_errors) = tokenize(&file_contents);
+
+    let expected_tokens = vec![
+        Token::new(TokenType::Error, "\"ok\\q\"", None, 1),
+        Token::new(TokenType::Error, "\"a\n\\u{zz}\"", None, 3),
+        Token::new(TokenType::Error, "\"\\u{110000}\"", None, 3),
+        Token::new(TokenType::Error, "\"\\u41\"", None, 3),
+        Token::new(TokenType::Eof, "", None, 3),
+    ];
+
+    let expected_token_errors = vec![
+        TokenizerError::new(&Code::INVALID_ESCAPE_SEQUENCE, 1, "Invalid escape sequence: \\q".to_string()),
+        TokenizerError::new(&Code::INVALID_UNICODE_ESCAPE, 3, "Invalid unicode escape sequence.".to_string()),
+        TokenizerError::new(&Code::INVALID_UNICODE_ESCAPE, 3, "Invalid unicode escape sequence.".to_string()),
+        TokenizerError::new(&Code::INVALID_UNICODE_ESCAPE, 3, "Invalid unicode escape sequence.".to_string()),
+    ];
+
+    assert_eq!(tokens, expected_tokens);
+    assert_eq!(token_errors, expected_token_errors);
+    assert_eq!(token_errors[0].column(), Some(4));
+}
+
+#[test]
+fn test_tokenize_string_escaped_quote_does_not_terminate() {
+    let file_contents = String::from("\"a\\\"");
+    let (tokens, token_errors) = tokenize(&file_contents);
+
+    let expected_token_errors = vec![
+        TokenizerError::new(&Code::UNTERMINATED_STRING, 1, "Unterminated string.".to_string()),
+    ];
+
+    let expected_tokens = vec![
+        Token::new(TokenType::Error, "\"a\\\"", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
+    ];
+
+    assert_eq!(tokens, expected_tokens);
+    assert_eq!(token_errors, expected_token_errors);
+}
+
+#[test]
+fn test_tokenize_block_comments() {
+    let file_contents = String::from("( /* one\n/* two */ still\n*/ ) /**/ /***/ *");
+    let (tokens, token_errors) = tokenize(&file_contents);
+
+    let expected_tokens = vec![
+        Token::new(TokenType::LeftParen, "(", None, 1),
+        Token::new(TokenType::RightParen, ")", None, 3),
+        Token::new(TokenType::Star, "*", None, 3),
+        Token::new(TokenType::Eof, "", None, 3),
+    ];
+
+    assert_eq!(tokens, expected_tokens);
+    assert_eq!(token_errors, vec![]);
+}
+
+#[test]
+fn test_tokenize_unterminated_block_comment() {
+    let file_contents = String::from("+\n/* outer /* inner */\n\n");
+    let (tokens, token_errors) = tokenize(&file_contents);
+
+    let expected_tokens = vec![
+        Token::new(TokenType::Plus, "+", None, 1),
+        Token::new(TokenType::Error, "/* outer /* inner */\n\n", None, 4),
+        Token::new(TokenType::Eof, "", None, 4),
+    ];
+
+    let expected_token_errors = vec![
+        TokenizerError::new(&Code::UNTERMINATED_BLOCK_COMMENT, 2, "Unterminated block comment.".to_string()),
+    ];
+
+    assert_eq!(tokens, expected_tokens);
+    assert_eq!(token_errors, expected_token_errors);
+}
+
+#[test]
+fn test_tokenize_number_syntax() {
+    let file_contents = String::from("0xFF 0b1010 1e-9 2.5E+3 1_000_000 0x_1 12.5.");
+    let (tokens, token_errors) = tokenize(&file_contents);
+
+    let expected_tokens = vec![
+        Token::new_number(TokenType::Number, "0xFF", Some(255.0), 1),
+        Token::new_number(TokenType::Number, "0b1010", Some(10.0), 1),
+        Token::new_number(TokenType::Number, "1e-9", Some(1e-9), 1),
+        Token::new_number(TokenType::Number, "2.5E+3", Some(2500.0), 1),
+        Token::new_number(TokenType::Number, "1_000_000", Some(1_000_000.0), 1),
+        Token::new(TokenType::Error, "0x_1", None, 1),
+        Token::new_number(TokenType::Number, "12.5", Some(12.5), 1),
+        Token::new(TokenType::Dot, ".", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
+    ];
+
+    let expected_token_errors = vec![
+        TokenizerError::new(&Code::MALFORMED_NUMBER, 1, "Malformed number literal: 0x_1".to_string()),
+    ];
+
+    assert_eq!(tokens, expected_tokens);
+    assert_eq!(token_errors, expected_token_errors);
+}
+
+#[test]
+fn test_tokenize_malformed_numbers() {
+    let file_contents = String::from("0x 1e 1e+ 0b102 1_ 1__2 3_.5");
+    let (tokens, token_errors) = tokenize(&file_contents);
 
     let expected_tokens = vec![
-        Token::new(TokenType::If, "if".to_string(), None, 1),
-        Token::new(TokenType::Else, "else".to_string(), None, 1),
-        Token::new(TokenType::Return, "return".to_string(), None, 1),
-        Token::new(TokenType::Eof, "".to_string(), None, 1),
+        Token::new(TokenType::Error, "0x", None, 1),
+        Token::new(TokenType::Error, "1e", None, 1),
+        Token::new(TokenType::Error, "1e+", None, 1),
+        Token::new(TokenType::Error, "0b102", None, 1),
+        Token::new(TokenType::Error, "1_", None, 1),
+        Token::new(TokenType::Error, "1__2", None, 1),
+        Token::new(TokenType::Error, "3_.5", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
+    ];
+
+    let expected_token_errors = vec![
+        TokenizerError::new(&Code::MALFORMED_NUMBER, 1, "Malformed number literal: 0x".to_string()),
+        TokenizerError::new(&Code::MALFORMED_NUMBER, 1, "Malformed number literal: 1e".to_string()),
+        TokenizerError::new(&Code::MALFORMED_NUMBER, 1, "Malformed number literal: 1e+".to_string()),
+        TokenizerError::new(&Code::MALFORMED_NUMBER, 1, "Malformed number literal: 0b102".to_string()),
+        TokenizerError::new(&Code::MALFORMED_NUMBER, 1, "Malformed number literal: 1_".to_string()),
+        TokenizerError::new(&Code::MALFORMED_NUMBER, 1, "Malformed number literal: 1__2".to_string()),
+        TokenizerError::new(&Code::MALFORMED_NUMBER, 1, "Malformed number literal: 3_.5".to_string()),
     ];
 
     assert_eq!(tokens, expected_tokens);
+    assert_eq!(token_errors, expected_token_errors);
+}
+
+#[test]
+fn test_tokenize_unicode_identifiers() {
+    let file_contents = String::from("año π_2 переменная 変数 متغير ⅷ _x a·b");
+    let (tokens, token_errors) = tokenize(&file_contents);
+
+    let expected_tokens = vec![
+        Token::new(TokenType::Identifier, "año", None, 1),
+        Token::new(TokenType::Identifier, "π_2", None, 1),
+        Token::new(TokenType::Identifier, "переменная", None, 1),
+        Token::new(TokenType::Identifier, "変数", None, 1),
+        Token::new(TokenType::Identifier, "متغير", None, 1),
+        Token::new(TokenType::Identifier, "ⅷ", None, 1),
+        Token::new(TokenType::Identifier, "_x", None, 1),
+        Token::new(TokenType::Identifier, "a·b", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
+    ];
+
+    assert_eq!(tokens, expected_tokens);
+    assert_eq!(token_errors, vec![]);
+}
+
+#[test]
+fn test_tokenize_identifiers_rejects_non_xid() {
+    // U+0301 (combining acute) may continue but not start an identifier,
+    // and "²" is not an identifier character at all.
+    let file_contents = String::from("\u{301}e x²");
+    let (tokens, token_errors) = tokenize(&file_contents);
+
+    let expected_tokens = vec![
+        Token::new(TokenType::Error, "\u{301}", None, 1),
+        Token::new(TokenType::Identifier, "e", None, 1),
+        Token::new(TokenType::Identifier, "x", None, 1),
+        Token::new(TokenType::Error, "²", None, 1),
+        Token::new(TokenType::Eof, "", None, 1),
+    ];
+
+    let expected_token_errors = vec![
+        TokenizerError::new(&Code::UNEXPECTED_CHARACTER, 1, "Unexpected character: \u{301}".to_string()),
+        TokenizerError::new(&Code::UNEXPECTED_CHARACTER, 1, "Unexpected character: ²".to_string()),
+    ];
+
+    assert_eq!(tokens, expected_tokens);
+    assert_eq!(token_errors, expected_token_errors);
+}
+
+#[test]
+fn test_tokenize_identifiers_nfc_names() {
+    // The same word, precomposed and with a combining tilde.
+    let file_contents = String::from("a\u{f1}o an\u{303}o");
+    let (tokens, token_errors) = tokenize(&file_contents);
+
+    assert_eq!(tokens[0].lexeme(), "a\u{f1}o");
+    assert_eq!(tokens[1].lexeme(), "an\u{303}o");
+    assert_eq!(tokens[0].name(), tokens[1].name());
+    assert_eq!(tokens[1].name(), "a\u{f1}o");
+    assert_eq!(tokens[0], tokens[1]);
     assert_eq!(token_errors, vec![]);
 }
@@ -1,64 +1,184 @@
-use crate::tokenize::{Token, TokenType, TokenizerError};
-
-use std::collections::HashMap;
-
-
-#[derive(Debug)]
-pub struct Scanner {
-    source: String,
-    tokens: Vec<Token>,
-    token_errors: Vec<TokenizerError>,
-
-    start: u32,
-    current: u32,
+use crate::diagnostics::Code;
+use crate::tokenize::{Position, Span, Token, TokenType, TokenizerError};
+
+use std::borrow::Cow;
+
+use unicode_ident::{is_xid_continue, is_xid_start};
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+/// The reserved words, with the token type each one scans to.
+pub const KEYWORDS: [(&str, TokenType); 16] = [
+    ("and", TokenType::And),
+    ("class", TokenType::Class),
+    ("else", TokenType::Else),
+    ("false", TokenType::False),
+    ("fun", TokenType::Fun),
+    ("for", TokenType::For),
+    ("if", TokenType::If),
+    ("nil", TokenType::Nil),
+    ("or", TokenType::Or),
+    ("print", TokenType::Print),
+    ("return", TokenType::Return),
+    ("super", TokenType::Super),
+    ("this", TokenType::This),
+    ("true", TokenType::True),
+    ("var", TokenType::Var),
+    ("while", TokenType::While),
+];
+
+pub type ScanResult<'src> = Result<Token<'src>, TokenizerError>;
+
+#[derive(Debug, Clone)]
+pub struct Scanner<'src> {
+    source: &'src str,
+
+    // `start` and `current` are byte offsets into `source`, always kept on
+    // a char boundary so that slicing the lexeme never panics.
+    start: usize,
+    current: usize,
     line: u32,
+    // 1-based char column of `current` within its line.
+    column: u32,
+    start_position: Position,
+
+    // When set, the whitespace and comments around each token are attached
+    // to it instead of being dropped. `trivia_start` is where the leading
+    // trivia of the next token begins.
+    trivia: bool,
+    trivia_start: usize,
+    error_token: Option<Token<'src>>,
 }
 
-#[allow(dead_code)]
-impl Scanner {
-    pub fn new(source: String) -> Self {
+impl<'src> Scanner<'src> {
+    pub fn new(source: &'src str) -> Self {
         Self {
             source,
-            tokens: Vec::new(),
-            token_errors: Vec::new(),
 
             start: 0,
             current: 0,
             line: 1,
+            column: 1,
+            start_position: Position::default(),
+
+            trivia: false,
+            trivia_start: 0,
+            error_token: None,
         }
     }
 
-    pub fn scan_tokens(&mut self) -> Vec<Token> {
+    /// Switches on trivia collection, see `Token::leading_trivia`.
+    pub fn with_trivia(mut self) -> Self {
+        self.trivia = true;
+        self
+    }
+
+    /// Scans the next token or error, skipping whitespace and comments.
+    /// Returns `None` once the whole source has been consumed.
+    pub fn next_token(&mut self) -> Option<ScanResult<'src>> {
+        self.trivia_start = self.current;
+
         while !self.is_at_end() {
             self.start = self.current;
-            self.scan_token();
+            self.start_position = self.position();
+
+            let Some(mut result) = self.scan_token() else {
+                continue;
+            };
+
+            if result.is_err() {
+                self.error_token = Some(
+                    Token::new(
+                        TokenType::Error,
+                        self.lexeme(),
+                        None,
+                        self.line
+                    ).with_span(self.span())
+                );
+            }
+
+            if self.trivia {
+                let leading = &self.source[self.trivia_start..self.start];
+                let trailing = self.trailing_trivia();
+
+                let token = match &mut result {
+                    Ok(token) => Some(token),
+                    Err(_) => self.error_token.as_mut(),
+                };
+                if let Some(token) = token {
+                    token.set_trivia(leading, trailing);
+                }
+            }
+
+            return Some(result);
         }
 
-        self.tokens.push(
-            Token::new(
-                TokenType::Eof,
-                "".to_string(),
-                None,
-                self.line
-            )
-        );
+        None
+    }
+
+    pub fn eof_token(&mut self) -> Token<'src> {
+        self.start = self.current;
+        self.start_position = self.position();
+
+        let mut token = Token::new(
+            TokenType::Eof,
+            "",
+            None,
+            self.line
+        ).with_span(self.span());
 
-        self.tokens.clone()
+        if self.trivia {
+            token.set_trivia(&self.source[self.trivia_start..self.start], "");
+        }
+
+        token
     }
 
-    pub fn token_errors(&self) -> Vec<TokenizerError> {
-        self.token_errors.clone()
+    /// Takes the `Error` token standing in for the lexeme that produced the
+    /// last `TokenizerError`.
+    pub fn take_error_token(&mut self) -> Option<Token<'src>> {
+        self.error_token.take()
     }
 
+    /// Consumes the whitespace and line comment that follow a token on its
+    /// own line. Everything else, newlines and block comments included,
+    /// becomes leading trivia of the next token.
+    fn trailing_trivia(&mut self) -> &'src str {
+        let trailing_start = self.current;
+
+        loop {
+            match self.peek() {
+                ' ' | '\r' | '\t' if !self.is_at_end() => {
+                    self.advance();
+                }
+                '/' if self.peek_next() == '/' => {
+                    while self.peek() != '\n' && !self.is_at_end() {
+                        self.advance();
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        &self.source[trailing_start..self.current]
+    }
+
+    fn position(&self) -> Position {
+        Position::new(self.line, self.column, self.current)
+    }
+
+    /// The range from the start of the current lexeme up to the cursor.
+    fn span(&self) -> Span {
+        Span::new(self.start_position, self.position())
+    }
 
     fn is_at_end(&self) -> bool {
-        self.current >= self.source.len() as u32
+        self.current >= self.source.len()
     }
 
-    fn scan_token(&mut self) {
+    fn scan_token(&mut self) -> Option<ScanResult<'src>> {
         let c = self.advance();
 
-        match c {
+        let result = match c {
 
             // Single-character tokens
 
@@ -78,9 +198,15 @@ impl Scanner {
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
-                } else {
-                    self.add_token(TokenType::Slash, None);
+
+                    return None;
+                }
+
+                if self.match_char('*') {
+                    return self.block_comment();
                 }
+
+                self.add_token(TokenType::Slash, None)
             }
 
             // Operator tokens
@@ -91,7 +217,7 @@ impl Scanner {
                 } else {
                     TokenType::Bang
                 };
-                self.add_token(token_type, None);
+                self.add_token(token_type, None)
             }
             '=' => {
                 let token_type = if self.match_char('=') {
@@ -99,7 +225,7 @@ impl Scanner {
                 } else {
                     TokenType::Equal
                 };
-                self.add_token(token_type, None);
+                self.add_token(token_type, None)
             }
             '<' => {
                 let token_type = if self.match_char('=') {
@@ -107,7 +233,7 @@ impl Scanner {
                 } else {
                     TokenType::Less
                 };
-                self.add_token(token_type, None);
+                self.add_token(token_type, None)
             }
             '>' => {
                 let token_type = if self.match_char('=') {
@@ -115,168 +241,360 @@ impl Scanner {
                 } else {
                     TokenType::Greater
                 };
-                self.add_token(token_type, None);
+                self.add_token(token_type, None)
             }
 
-            ' ' | '\r' | '\t' => { /* ignore whitespace */ },
-            '\n' => self.line += 1,
+            // ignore whitespace, `advance` already counted the newline
+            ' ' | '\r' | '\t' | '\n' => return None,
 
             // Literals
 
             '"' => self.string(),
             '0'..='9' => self.number(),
-            'a'..='z' | 'A'..='Z' | '_' => self.identifier(),
+            c if is_xid_start(c) || c == '_' => self.identifier(),
 
             _ => {
-                self.add_token_error(
+                self.token_error(
+                    &Code::UNEXPECTED_CHARACTER,
                     format!("Unexpected character: {}", c)
-                );
+                )
             }
-        }
+        };
+
+        Some(result)
     }
 
     fn advance(&mut self) -> char {
-        let c = self.source.chars().nth(self.current as usize).unwrap_or('\0');
-        self.current += 1;
+        let c = self.peek();
+        self.current += c.len_utf8();
+
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
 
         c
     }
 
-    fn add_token(&mut self, token_type: TokenType, literal: Option<String>) {
-        let text = self.source[self.start as usize..self.current as usize].to_string();
+    fn lexeme(&self) -> &'src str {
+        &self.source[self.start..self.current]
+    }
 
-        self.tokens.push(
+    fn add_token(&self, token_type: TokenType, literal: Option<Cow<'src, str>>) -> ScanResult<'src> {
+        Ok(
             Token::new(
                 token_type,
-                text,
+                self.lexeme(),
                 literal,
                 self.line
-            )
-        );
+            ).with_span(self.span())
+        )
     }
 
-    fn add_token_number(&mut self, token_type: TokenType, literal: Option<f64>) {
-        let text = self.source[self.start as usize..self.current as usize].to_string();
-
-        self.tokens.push(
+    fn add_token_number(&self, token_type: TokenType, literal: Option<f64>) -> ScanResult<'src> {
+        Ok(
             Token::new_number(
                 token_type,
-                text,
+                self.lexeme(),
                 literal,
                 self.line
-            )
-        );
+            ).with_span(self.span())
+        )
     }
 
-    fn add_token_error(&mut self, message: String) {
-        self.token_errors.push(
+    fn token_error(&self, code: &'static Code, message: String) -> ScanResult<'src> {
+        Err(
             TokenizerError::new(
+                code,
                 self.line,
                 message
-            )
-        );
+            ).with_span(self.span())
+        )
     }
 
     fn match_char(&mut self, expected: char) -> bool {
-        let index = self.current as usize;
-
         if self.is_at_end() { return false; }
+        if self.peek() != expected { return false; }
 
-        let c = self.source[index..index + 1].to_string();
-        if c != expected.to_string() { return false; }
-
-        self.current += 1;
+        self.advance();
         true
     }
 
     fn peek(&self) -> char {
-        if self.is_at_end() { return '\0'; }
-
-        self.source.chars().nth(self.current as usize).unwrap_or('\0')
+        self.source[self.current..].chars().next().unwrap_or('\0')
     }
 
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() as u32 { return '\0'; }
+        let mut chars = self.source[self.current..].chars();
+        chars.next();
 
-        self.source.chars().nth((self.current + 1) as usize).unwrap_or('\0')
+        chars.next().unwrap_or('\0')
     }
 
-    fn string(&mut self) {
+    fn string(&mut self) -> ScanResult<'src> {
+        // The literal borrows the source until an escape forces a copy.
+        let mut value: Option<String> = None;
+        let mut segment_start = self.current;
+        let mut escape_error = None;
+
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' { self.line += 1; }
+            if self.peek() != '\\' {
+                self.advance();
+                continue;
+            }
+
+            let value = value.get_or_insert_with(String::new);
+            value.push_str(&self.source[segment_start..self.current]);
 
+            let escape_start = self.position();
             self.advance();
+
+            match self.escape() {
+                Ok(c) => value.push(c),
+                Err(token_error) => {
+                    // Keep the first bad escape, but still scan up to the
+                    // closing quote so the rest of the string is skipped.
+                    escape_error.get_or_insert(
+                        token_error.with_span(Span::new(escape_start, self.position()))
+                    );
+                }
+            }
+
+            segment_start = self.current;
         }
 
         if self.is_at_end() {
-            self.add_token_error(
+            return self.token_error(
+                &Code::UNTERMINATED_STRING,
                 "Unterminated string.".to_string()
             );
-
-            return;
         }
 
         // The closing ".
         self.advance();
 
+        if let Some(escape_error) = escape_error {
+            return Err(escape_error);
+        }
+
         // Trim the surrounding quotes.
-        let value = self.source[
-            (self.start as usize + 1)..(self.current as usize - 1)
-        ].to_string();
-        self.add_token(TokenType::String, Some(value));
+        let value = match value {
+            Some(mut value) => {
+                value.push_str(&self.source[segment_start..(self.current - 1)]);
+                Cow::Owned(value)
+            }
+            None => Cow::Borrowed(&self.source[(self.start + 1)..(self.current - 1)]),
+        };
+        self.add_token(TokenType::String, Some(value))
     }
 
-    fn number(&mut self) {
-        while self.peek().is_ascii_digit() {
+    /// Skips a `/* ... */` comment, which may span lines and nest. Only an
+    /// unterminated comment produces a result, reported on the line where
+    /// the comment was opened.
+    fn block_comment(&mut self) -> Option<ScanResult<'src>> {
+        let mut depth = 1;
+
+        while depth > 0 {
+            if self.is_at_end() {
+                return Some(Err(
+                    TokenizerError::new(
+                        &Code::UNTERMINATED_BLOCK_COMMENT,
+                        self.start_position.line(),
+                        "Unterminated block comment.".to_string()
+                    ).with_span(self.span())
+                ));
+            }
+
+            if self.peek() == '/' && self.peek_next() == '*' {
+                self.advance();
+                self.advance();
+                depth += 1;
+            } else if self.peek() == '*' && self.peek_next() == '/' {
+                self.advance();
+                self.advance();
+                depth -= 1;
+            } else {
+                self.advance();
+            }
+        }
+
+        None
+    }
+
+    /// Decodes the escape sequence following a backslash, or returns the
+    /// error without a span, which the caller knows.
+    fn escape(&mut self) -> Result<char, TokenizerError> {
+        if self.is_at_end() {
+            return Err(
+                TokenizerError::new(&Code::UNTERMINATED_STRING, self.line, "Unterminated string.".to_string())
+            );
+        }
+
+        match self.advance() {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            'u' => self.unicode_escape(),
+            c => Err(
+                TokenizerError::new(
+                    &Code::INVALID_ESCAPE_SEQUENCE,
+                    self.line,
+                    format!("Invalid escape sequence: \\{}", c.escape_debug())
+                )
+            ),
+        }
+    }
+
+    /// Decodes the `{XXXX}` part of a `\u{XXXX}` escape, with 1 to 6 hex
+    /// digits naming a Unicode scalar value.
+    fn unicode_escape(&mut self) -> Result<char, TokenizerError> {
+        let line = self.line;
+        let error = || Err(
+            TokenizerError::new(&Code::INVALID_UNICODE_ESCAPE, line, "Invalid unicode escape sequence.".to_string())
+        );
+
+        if !self.match_char('{') {
+            return error();
+        }
+
+        let digits_start = self.current;
+        while self.peek().is_ascii_hexdigit() {
             self.advance();
         }
+        let digits = &self.source[digits_start..self.current];
+
+        if digits.is_empty() || digits.len() > 6 || !self.match_char('}') {
+            return error();
+        }
+
+        match u32::from_str_radix(digits, 16).ok().and_then(char::from_u32) {
+            Some(c) => Ok(c),
+            None => error(),
+        }
+    }
+
+    fn number(&mut self) -> ScanResult<'src> {
+        match (self.lexeme(), self.peek()) {
+            ("0", 'x' | 'X') => return self.radix_number(16),
+            ("0", 'b' | 'B') => return self.radix_number(2),
+            _ => {}
+        }
+
+        let mut well_formed = self.digits(10, true);
 
         // Look for a fractional part.
         if self.peek() == '.' && self.peek_next().is_ascii_digit() {
             // Consume the "."
             self.advance();
 
-            while self.peek().is_ascii_digit() {
+            well_formed &= self.digits(10, false);
+        }
+
+        // Look for an exponent.
+        if matches!(self.peek(), 'e' | 'E') {
+            self.advance();
+
+            if matches!(self.peek(), '+' | '-') {
                 self.advance();
             }
+
+            well_formed &= self.digits(10, false);
+        }
+
+        if !well_formed {
+            return self.malformed_number();
         }
 
-        let value = self.source[
-            (self.start as usize)..(self.current as usize)
-        ].to_string();
-        let float_value = value.parse::<f64>().unwrap();
-        self.add_token_number(TokenType::Number, Some(float_value));
+        let float_value = match self.lexeme().contains('_') {
+            true => self.lexeme().replace('_', "").parse::<f64>(),
+            false => self.lexeme().parse::<f64>(),
+        };
+
+        match float_value {
+            Ok(float_value) => self.add_token_number(TokenType::Number, Some(float_value)),
+            Err(_) => self.malformed_number(),
+        }
     }
 
-    fn identifier(&mut self) {
-        while self.peek().is_alphanumeric() || self.peek() == '_' {
+    /// Scans the digits of a `0x` or `0b` literal, whose leading `0` has
+    /// already been consumed.
+    fn radix_number(&mut self, radix: u32) -> ScanResult<'src> {
+        // The "x" or "b".
+        self.advance();
+
+        let digits_start = self.current;
+        let well_formed = self.digits(radix, false);
+
+        // Digits of the wrong base belong to the same, malformed, literal.
+        if !well_formed || self.peek().is_ascii_alphanumeric() {
+            while self.peek().is_ascii_alphanumeric() || self.peek() == '_' {
+                self.advance();
+            }
+
+            return self.malformed_number();
+        }
+
+        let float_value = self.source[digits_start..self.current]
+            .chars()
+            .filter_map(|c| c.to_digit(radix))
+            .fold(0.0, |value, digit| value * radix as f64 + digit as f64);
+
+        self.add_token_number(TokenType::Number, Some(float_value))
+    }
+
+    /// Consumes a run of digits in `radix`, optionally separated by `_`, and
+    /// reports whether it is well formed: not empty and with every `_` placed
+    /// between two digits. `after_digit` tells that the digit right before
+    /// the run was already consumed.
+    fn digits(&mut self, radix: u32, after_digit: bool) -> bool {
+        let run_start = self.current;
+        while self.peek().is_digit(radix) || self.peek() == '_' {
+            self.advance();
+        }
+        let run = &self.source[run_start..self.current];
+
+        if run.is_empty() {
+            return after_digit;
+        }
+
+        !run.ends_with('_') && !run.contains("__") && (after_digit || !run.starts_with('_'))
+    }
+
+    fn malformed_number(&self) -> ScanResult<'src> {
+        self.token_error(
+            &Code::MALFORMED_NUMBER,
+            format!("Malformed number literal: {}", self.lexeme())
+        )
+    }
+
+    /// Scans an identifier as defined by UAX #31 (`XID_Start XID_Continue*`,
+    /// also allowing a leading `_`). Its name is compared in NFC, so the
+    /// same word written with precomposed or combining characters is the
+    /// same identifier.
+    fn identifier(&mut self) -> ScanResult<'src> {
+        while is_xid_continue(self.peek()) {
             self.advance();
         }
 
-        let text = self.source[
-            (self.start as usize)..(self.current as usize)
-        ].to_string();
-
-        let token_type = match text.as_str() {
-            "and" => TokenType::And,
-            "class" => TokenType::Class,
-            "else" => TokenType::Else,
-            "false" => TokenType::False,
-            "fun" => TokenType::Fun,
-            "for" => TokenType::For,
-            "if" => TokenType::If,
-            "nil" => TokenType::Nil,
-            "or" => TokenType::Or,
-            "print" => TokenType::Print,
-            "return" => TokenType::Return,
-            "super" => TokenType::Super,
-            "this" => TokenType::This,
-            "true" => TokenType::True,
-            "var" => TokenType::Var,
-            "while" => TokenType::While,
-            _ => TokenType::Identifier
+        let lexeme = self.lexeme();
+        let name = match is_nfc_quick(lexeme.chars()) {
+            IsNormalized::Yes => Cow::Borrowed(lexeme),
+            _ => Cow::Owned(lexeme.nfc().collect::<String>()),
         };
 
-        self.add_token(token_type, None);
+        let token_type = KEYWORDS
+            .iter()
+            .find(|(keyword, _)| *keyword == name)
+            .map_or(TokenType::Identifier, |(_, token_type)| token_type.clone());
+
+        let token = self.add_token(token_type, None)?;
+        match name {
+            Cow::Owned(name) if name != lexeme => Ok(token.with_name(name)),
+            _ => Ok(token),
+        }
     }
 }
@@ -0,0 +1,82 @@
+/// A point in the source text.
+///
+/// `line` and `column` are 1-based and count chars, which is what an editor
+/// shows to the user; `offset` is the 0-based byte offset into the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    line: u32,
+    column: u32,
+    offset: usize,
+}
+
+impl Position {
+    pub fn new(line: u32, column: u32, offset: usize) -> Self {
+        Self {
+            line,
+            column,
+            offset,
+        }
+    }
+
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::new(1, 1, 0)
+    }
+}
+
+/// The half-open range `[start, end)` of source text covered by a token or
+/// an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    start: Position,
+    end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Self {
+            start,
+            end,
+        }
+    }
+
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    pub fn end(&self) -> Position {
+        self.end
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.offset - self.start.offset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[test]
+fn test_span_len() {
+    let span = Span::new(
+        Position::new(1, 3, 2),
+        Position::new(1, 5, 6),
+    );
+
+    assert_eq!(span.len(), 4);
+    assert!(!span.is_empty());
+}
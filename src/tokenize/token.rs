@@ -1,63 +1,148 @@
 use super::token_type::TokenType;
+use super::span::Span;
+
+use std::borrow::Cow;
+use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
-enum LiteralType {
-    String(String),
+pub enum LiteralType<'src> {
+    String(Cow<'src, str>),
     Number(f64),
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct Token {
+/// A token borrowing its lexeme from the source it was scanned from.
+#[derive(Debug, Clone)]
+pub struct Token<'src> {
     token_type: TokenType,
-    lexeme: String,
-    literal: Option<LiteralType>,
+    lexeme: &'src str,
+    literal: Option<LiteralType<'src>>,
+    // Only set for identifiers whose lexeme is not already in NFC.
+    normalized_name: Option<String>,
 
-    #[allow(dead_code)]
     line: u32,
+    // `None` until the scanner sets it, for tokens built without a source.
+    span: Option<Span>,
+
+    leading_trivia: &'src str,
+    trailing_trivia: &'src str,
 }
 
-#[allow(dead_code)]
-impl Token {
-    pub fn new(token_type: TokenType, lexeme: String, literal: Option<String>, line: u32) -> Self {
-        let literal = match literal {
-            Some(literal) => Some(
-                LiteralType::String(literal),
-            ),
-            None => None
-        };
+impl<'src> Token<'src> {
+    pub fn new(token_type: TokenType, lexeme: &'src str, literal: Option<Cow<'src, str>>, line: u32) -> Self {
+        let literal = literal.map(LiteralType::String);
 
         Self {
             token_type,
             lexeme,
             literal,
+            normalized_name: None,
             line,
+            span: None,
+
+            leading_trivia: "",
+            trailing_trivia: "",
         }
     }
 
-    pub fn new_number(token_type: TokenType, lexeme: String, literal: Option<f64>, line: u32) -> Self {
-        let literal = match literal {
-            Some(literal) => Some(
-                LiteralType::Number(literal),
-            ),
-            None => None
-        };
+    pub fn new_number(token_type: TokenType, lexeme: &'src str, literal: Option<f64>, line: u32) -> Self {
+        let literal = literal.map(LiteralType::Number);
 
         Self {
             token_type,
             lexeme,
             literal,
+            normalized_name: None,
             line,
+            span: None,
+
+            leading_trivia: "",
+            trailing_trivia: "",
         }
     }
 
-    pub fn to_string(&self) -> String {
+    /// Attaches the source range the token was scanned from.
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Sets the NFC form of an identifier whose lexeme is not normalized.
+    pub fn with_name(mut self, name: String) -> Self {
+        self.normalized_name = Some(name);
+        self
+    }
+
+    pub(crate) fn set_trivia(&mut self, leading: &'src str, trailing: &'src str) {
+        self.leading_trivia = leading;
+        self.trailing_trivia = trailing;
+    }
+
+    pub fn token_type(&self) -> &TokenType {
+        &self.token_type
+    }
+
+    pub fn lexeme(&self) -> &'src str {
+        self.lexeme
+    }
+
+    /// The text identifiers are compared by: the NFC form of the lexeme.
+    /// For every other token, and for identifiers already in NFC, this is
+    /// just the lexeme.
+    pub fn name(&self) -> &str {
+        self.normalized_name.as_deref().unwrap_or(self.lexeme)
+    }
+
+    pub fn literal(&self) -> Option<&LiteralType<'src>> {
+        self.literal.as_ref()
+    }
+
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    pub fn column(&self) -> Option<u32> {
+        self.span.map(|span| span.start().column())
+    }
+
+    /// Whitespace, newlines and comments between the previous token and
+    /// this one. Always empty unless the lexer was built `with_trivia`.
+    pub fn leading_trivia(&self) -> &'src str {
+        self.leading_trivia
+    }
+
+    /// Whitespace and a line comment following this token on its line.
+    /// Always empty unless the lexer was built `with_trivia`.
+    pub fn trailing_trivia(&self) -> &'src str {
+        self.trailing_trivia
+    }
+}
+
+// The span and trivia are left out of the comparison, so two tokens with the
+// same content on the same line are equal. Identifiers compare by their NFC
+// name rather than by the raw lexeme.
+impl PartialEq for Token<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.token_type == other.token_type
+            && self.name() == other.name()
+            && self.literal == other.literal
+            && self.line == other.line
+    }
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let text_literal = match &self.literal {
-            Some(LiteralType::String(literal)) => literal.clone(),
+            Some(LiteralType::String(literal)) => literal.to_string(),
             Some(LiteralType::Number(literal)) => format!("{:?}", literal),
             None => "null".to_string(),
         };
 
-        format!(
+        write!(
+            f,
             "{} {} {}",
             self.token_type,
             self.lexeme,
@@ -78,7 +163,7 @@ fn test_token_type() {
 fn test_token() {
     let token = Token::new(
         TokenType::LeftParen,
-        "(".to_string(),
+        "(",
         None,
         0
     );
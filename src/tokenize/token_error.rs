@@ -1,21 +1,65 @@
-#[derive(Debug, Clone, PartialEq)]
+use crate::diagnostics::Code;
+use super::span::Span;
+
+use std::fmt;
+
+#[derive(Debug, Clone)]
 pub struct TokenizerError {
+    code: &'static Code,
     line: u32,
-    // where: String,
+    // `None` until the scanner sets it, for errors built without a source.
+    span: Option<Span>,
     message: String,
 }
 
-#[allow(dead_code)]
 impl TokenizerError {
-    pub fn new(line: u32, message: String) -> Self {
+    pub fn new(code: &'static Code, line: u32, message: String) -> Self {
         Self {
+            code,
             line,
+            span: None,
             message,
         }
     }
 
-    pub fn to_string(&self) -> String {
-        format!(
+    /// Attaches the source range the error refers to.
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn code(&self) -> &'static Code {
+        self.code
+    }
+
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    pub fn column(&self) -> Option<u32> {
+        self.span.map(|span| span.start().column())
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+// As with `Token`, the span is not part of the comparison.
+impl PartialEq for TokenizerError {
+    fn eq(&self, other: &Self) -> bool {
+        self.code == other.code && self.line == other.line && self.message == other.message
+    }
+}
+
+impl fmt::Display for TokenizerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
             "[line {}] Error: {}",
             self.line,
             self.message
@@ -26,7 +70,7 @@ impl TokenizerError {
 #[test]
 fn test_tokenizer_error_unexpected_character_1() {
     let message = "Unexpected character: $".to_string();
-    let token_error = TokenizerError::new(1, message);
+    let token_error = TokenizerError::new(&Code::UNEXPECTED_CHARACTER, 1, message);
 
     let expected = "[line 1] Error: Unexpected character: $".to_string();
 
@@ -36,7 +80,7 @@ fn test_tokenizer_error_unexpected_character_1() {
 #[test]
 fn test_tokenizer_error_unexpected_character_2() {
     let message = "Unexpected character: #".to_string();
-    let token_error = TokenizerError::new(23, message);
+    let token_error = TokenizerError::new(&Code::UNEXPECTED_CHARACTER, 23, message);
 
     let expected = "[line 23] Error: Unexpected character: #".to_string();
 
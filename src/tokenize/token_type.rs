@@ -52,6 +52,8 @@ pub enum TokenType {
     Var,
     While,
 
+    Error, // is the lexeme of a `TokenizerError`, kept so the stream has no holes
+
     Eof // is end of file
 }
 
@@ -103,6 +105,8 @@ impl fmt::Display for TokenType {
             TokenType::Var => write!(f, "VAR"),
             TokenType::While => write!(f, "WHILE"),
 
+            TokenType::Error => write!(f, "ERROR"),
+
             TokenType::Eof => write!(f, "EOF"),
         }
     }
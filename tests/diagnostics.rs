@@ -0,0 +1,189 @@
+use pretty_assertions::assert_eq;
+
+use std::fs;
+use std::process::Command;
+
+/// Runs `source` with `args` and pretty diagnostics, returning stderr and
+/// the exit code.
+fn run_pretty(name: &str, source: &str, args: &[&str]) -> (String, i32) {
+    let path = std::env::temp_dir().join(format!("{}_{}.lox", name, std::process::id()));
+    fs::write(&path, source).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_interpreter-starter-rust"))
+        .args(args)
+        .arg("--diagnostics=pretty")
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let stderr = String::from_utf8(output.stderr)
+        .unwrap()
+        .replace(&path.display().to_string(), "test.lox");
+
+    (stderr, output.status.code().unwrap())
+}
+
+#[test]
+fn test_pretty_compile_errors() {
+    let source = "print 1 +;\nfun f() {\n  return;\n}\nprint \"ok\" $;\n";
+
+    assert_eq!(
+        run_pretty("pretty_lexer", source, &["run"]),
+        (
+            "\
+error[L0001]: Unexpected character: $
+ --> test.lox:5:12
+  |
+5 | print \"ok\" $;
+  |            ^
+
+"
+            .to_string(),
+            65
+        )
+    );
+
+    let expected = "\
+error[L0101]: Expect expression.
+ --> test.lox:1:10
+  |
+1 | print 1 +;
+  |          ^ found `;`
+
+";
+    let source = "print 1 +;\n";
+    assert_eq!(run_pretty("pretty_parser", source, &["run"]), (expected.to_string(), 65));
+    assert_eq!(
+        run_pretty("pretty_parser_vm", source, &["run", "--backend=vm"]),
+        (expected.to_string(), 65)
+    );
+}
+
+#[test]
+fn test_pretty_runtime_errors() {
+    let source = "var name = \"lox\";\nprint name + 1;\n";
+    let expected = "\
+error[L0303]: Operands must be two numbers or two strings.
+ --> test.lox:2:12
+  |
+2 | print name + 1;
+  |            ^
+  |
+  = note: `+` adds numbers or concatenates strings, it doesn't convert between them
+
+";
+
+    assert_eq!(run_pretty("pretty_runtime", source, &["run"]), (expected.to_string(), 70));
+    assert_eq!(
+        run_pretty("pretty_runtime_vm", source, &["run", "--backend=vm"]),
+        (expected.to_string(), 70)
+    );
+}
+
+#[test]
+fn test_pretty_warnings() {
+    let source = "fun f(a) {\n  var b = a;\n  var _c = a;\n  return a;\n}\nprint f(1);\n";
+    let expected = "\
+warning[L0209]: Local variable 'b' is never used.
+ --> test.lox:2:7
+  |
+2 |   var b = a;
+  |       ^
+  |
+  = help: remove it, or start its name with `_` to keep it
+
+";
+
+    // Warnings don't stop the program, or change its exit code.
+    assert_eq!(run_pretty("pretty_warning", source, &["run"]), (expected.to_string(), 0));
+    assert_eq!(
+        run_pretty("pretty_warning_vm", source, &["run", "--backend=vm"]),
+        (expected.to_string(), 0)
+    );
+
+    assert_eq!(
+        run_pretty("pretty_warning_allowed", source, &["run", "--allow=unused-variable"]),
+        (String::new(), 0)
+    );
+    assert_eq!(
+        run_pretty("pretty_error_allowed", source, &["run", "--allow=L0101"]),
+        ("Only warnings and notes can be allowed: L0101 expected-expression\n".to_string(), 64)
+    );
+}
+
+#[test]
+fn test_pretty_notes() {
+    let source = "fun f(a) {\n  if (a) {\n    var a = 2;\n    print a;\n  }\n}\nf(true);\n";
+    let expected = "\
+note[L0210]: Local variable 'a' shadows a variable of an enclosing scope.
+ --> test.lox:3:9
+  |
+3 |     var a = 2;
+  |         ^
+1 | fun f(a) {
+  |       - shadowed variable declared here
+  |
+  = help: rename one of them if both are meant to be used
+
+";
+
+    assert_eq!(run_pretty("pretty_note", source, &["run"]), (expected.to_string(), 0));
+    assert_eq!(
+        run_pretty("pretty_note_vm", source, &["run", "--backend=vm"]),
+        (expected.to_string(), 0)
+    );
+    assert_eq!(
+        run_pretty("pretty_note_allowed", source, &["run", "--allow=L0210"]),
+        (String::new(), 0)
+    );
+}
+
+#[test]
+fn test_plain_output_has_no_warnings() {
+    let path = std::env::temp_dir().join(format!("plain_warning_{}.lox", std::process::id()));
+    fs::write(&path, "{\n  var unused = 1;\n}\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_interpreter-starter-rust"))
+        .arg("run")
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn test_explain_codes() {
+    let explain = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_interpreter-starter-rust"))
+            .arg("explain")
+            .args(args)
+            .output()
+            .unwrap();
+
+        (String::from_utf8(output.stdout).unwrap(), output.status.code().unwrap())
+    };
+
+    let (listing, exit_code) = explain(&[]);
+    assert_eq!(exit_code, 0);
+    assert!(listing.starts_with("L0001 unexpected-character\nL0002 unterminated-string\n"));
+
+    let (explanation, exit_code) = explain(&["L0203"]);
+    assert_eq!(exit_code, 0);
+    assert_eq!(
+        explanation,
+        "\
+L0203 top-level-return (resolver error)
+
+`return` appeared outside of any function or method.
+
+    return 1;
+"
+    );
+    assert_eq!(explain(&["top-level-return"]), (explanation, 0));
+
+    assert_eq!(explain(&["L9999"]), (String::new(), 64));
+}
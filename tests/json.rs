@@ -0,0 +1,196 @@
+//! Pins the `--format=json` output of every command, see the `json` module
+//! for the schema.
+
+use pretty_assertions::assert_eq;
+
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Runs `command` on `source` with JSON output, returning stdout and the
+/// exit code.
+fn run_json(name: &str, command: &[&str], source: &str) -> (String, i32) {
+    let path = std::env::temp_dir().join(format!("{}_{}.lox", name, std::process::id()));
+    fs::write(&path, source).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_interpreter-starter-rust"))
+        .args(command)
+        .arg("--format=json")
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+
+    (String::from_utf8(output.stdout).unwrap(), output.status.code().unwrap())
+}
+
+#[test]
+fn test_json_tokenize() {
+    assert_eq!(
+        run_json("json_tokenize", &["tokenize"], "x @\n\"s\""),
+        (
+            "\
+{\"kind\":\"token\",\"type\":\"IDENTIFIER\",\"lexeme\":\"x\",\"literal\":null,\"line\":1,\"column\":1,\"span\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":2,\"offset\":1}}}
+{\"kind\":\"error\",\"phase\":\"lexer\",\"message\":\"Unexpected character: @\",\"line\":1,\"column\":3,\"span\":{\"start\":{\"line\":1,\"column\":3,\"offset\":2},\"end\":{\"line\":1,\"column\":4,\"offset\":3}},\"code\":\"L0001\",\"severity\":\"error\",\"label\":null,\"secondary\":[],\"notes\":[],\"help\":null}
+{\"kind\":\"token\",\"type\":\"STRING\",\"lexeme\":\"\\\"s\\\"\",\"literal\":\"s\",\"line\":2,\"column\":1,\"span\":{\"start\":{\"line\":2,\"column\":1,\"offset\":4},\"end\":{\"line\":2,\"column\":4,\"offset\":7}}}
+{\"kind\":\"token\",\"type\":\"EOF\",\"lexeme\":\"\",\"literal\":null,\"line\":2,\"column\":4,\"span\":{\"start\":{\"line\":2,\"column\":4,\"offset\":7},\"end\":{\"line\":2,\"column\":4,\"offset\":7}}}
+"
+            .to_string(),
+            65
+        )
+    );
+}
+
+#[test]
+fn test_json_parse_and_evaluate() {
+    assert_eq!(
+        run_json("json_parse", &["parse"], "-(1 + 2)"),
+        ("{\"kind\":\"expression\",\"text\":\"(- (group (+ 1.0 2.0)))\"}\n".to_string(), 0)
+    );
+    assert_eq!(
+        run_json("json_evaluate", &["evaluate"], "\"a\" + \"b\""),
+        ("{\"kind\":\"value\",\"text\":\"ab\"}\n".to_string(), 0)
+    );
+    assert_eq!(
+        run_json("json_parse_error", &["parse"], "(1"),
+        (
+            "{\"kind\":\"error\",\"phase\":\"parser\",\"message\":\"Expect ')' after expression.\",\"line\":1,\"column\":3,\"span\":{\"start\":{\"line\":1,\"column\":3,\"offset\":2},\"end\":{\"line\":1,\"column\":3,\"offset\":2}},\"code\":\"L0102\",\"severity\":\"error\",\"label\":\"found the end of the file\",\"secondary\":[],\"notes\":[],\"help\":null}\n"
+                .to_string(),
+            65
+        )
+    );
+}
+
+#[test]
+fn test_json_run() {
+    let source = "print \"one\";\nprint \"two\nlines\";\n{ var a = a; }\n";
+    assert_eq!(
+        run_json("json_resolve_error", &["run"], source),
+        (
+            "{\"kind\":\"error\",\"phase\":\"resolver\",\"message\":\"Can't read local variable in its own initializer.\",\"line\":4,\"column\":11,\"span\":{\"start\":{\"line\":4,\"column\":11,\"offset\":42},\"end\":{\"line\":4,\"column\":12,\"offset\":43}},\"code\":\"L0201\",\"severity\":\"error\",\"label\":null,\"secondary\":[],\"notes\":[],\"help\":\"give the new variable another name to read the outer one\"}\n"
+                .to_string(),
+            65
+        )
+    );
+
+    let source = "print \"one\";\nprint \"two\nlines\";\nprint -nil;\n";
+    let output = "{\"kind\":\"output\",\"text\":\"one\"}\n\
+                  {\"kind\":\"output\",\"text\":\"two\"}\n\
+                  {\"kind\":\"output\",\"text\":\"lines\"}\n";
+    let expected = (
+        format!(
+            "{}{}",
+            output,
+            "{\"kind\":\"error\",\"phase\":\"runtime\",\"message\":\"Operand must be a number.\",\"line\":4,\"column\":7,\"span\":{\"start\":{\"line\":4,\"column\":7,\"offset\":38},\"end\":{\"line\":4,\"column\":8,\"offset\":39}},\"code\":\"L0301\",\"severity\":\"error\",\"label\":null,\"secondary\":[],\"notes\":[],\"help\":null}\n"
+        ),
+        70,
+    );
+    assert_eq!(run_json("json_run", &["run"], source), expected);
+    assert_eq!(run_json("json_run_vm", &["run", "--backend=vm"], source), expected);
+}
+
+#[test]
+fn test_json_warnings() {
+    let source = "{\n  var a = 1;\n  var a = 2;\n}\n";
+    let expected = (
+        "{\"kind\":\"error\",\"phase\":\"resolver\",\"message\":\"Already a variable with this name in this scope.\",\"line\":3,\"column\":7,\"span\":{\"start\":{\"line\":3,\"column\":7,\"offset\":21},\"end\":{\"line\":3,\"column\":8,\"offset\":22}},\"code\":\"L0202\",\"severity\":\"error\",\"label\":\"declared again here\",\"secondary\":[{\"span\":{\"start\":{\"line\":2,\"column\":7,\"offset\":8},\"end\":{\"line\":2,\"column\":8,\"offset\":9}},\"text\":\"first declared here\"}],\"notes\":[],\"help\":null}\n"
+            .to_string(),
+        65,
+    );
+    assert_eq!(run_json("json_duplicate", &["run"], source), expected);
+    assert_eq!(run_json("json_duplicate_vm", &["run", "--backend=vm"], source), expected);
+
+    let source = "{\n  var a = 1;\n}\nprint 2;\n";
+    let expected = (
+        "{\"kind\":\"warning\",\"phase\":\"resolver\",\"message\":\"Local variable 'a' is never used.\",\"line\":2,\"column\":7,\"span\":{\"start\":{\"line\":2,\"column\":7,\"offset\":8},\"end\":{\"line\":2,\"column\":8,\"offset\":9}},\"code\":\"L0209\",\"severity\":\"warning\",\"label\":null,\"secondary\":[],\"notes\":[],\"help\":\"remove it, or start its name with `_` to keep it\"}\n\
+         {\"kind\":\"output\",\"text\":\"2\"}\n"
+            .to_string(),
+        0,
+    );
+    assert_eq!(run_json("json_warning", &["run"], source), expected);
+    assert_eq!(run_json("json_warning_vm", &["run", "--backend=vm"], source), expected);
+    assert_eq!(
+        run_json("json_warning_allowed", &["run", "--allow=L0209"], source),
+        ("{\"kind\":\"output\",\"text\":\"2\"}\n".to_string(), 0)
+    );
+
+    let source = "fun f(a) {\n  { var a = 2; print a; }\n}\nf(1);\n";
+    let expected = (
+        "{\"kind\":\"note\",\"phase\":\"resolver\",\"message\":\"Local variable 'a' shadows a variable of an enclosing scope.\",\"line\":2,\"column\":9,\"span\":{\"start\":{\"line\":2,\"column\":9,\"offset\":19},\"end\":{\"line\":2,\"column\":10,\"offset\":20}},\"code\":\"L0210\",\"severity\":\"note\",\"label\":null,\"secondary\":[{\"span\":{\"start\":{\"line\":1,\"column\":7,\"offset\":6},\"end\":{\"line\":1,\"column\":8,\"offset\":7}},\"text\":\"shadowed variable declared here\"}],\"notes\":[],\"help\":\"rename one of them if both are meant to be used\"}\n\
+         {\"kind\":\"output\",\"text\":\"2\"}\n"
+            .to_string(),
+        0,
+    );
+    assert_eq!(run_json("json_note", &["run"], source), expected);
+    assert_eq!(run_json("json_note_vm", &["run", "--backend=vm"], source), expected);
+}
+
+#[test]
+fn test_json_disassemble() {
+    assert_eq!(
+        run_json("json_disassemble", &["disassemble"], "print 1;"),
+        (
+            "{\"kind\":\"disassembly\",\"text\":\"== <script> ==\\n0000    1 OP_CONSTANT         0 '1'\\n0002    | OP_PRINT\\n0003    | OP_NIL\\n0004    | OP_RETURN\\n\"}\n"
+                .to_string(),
+            0
+        )
+    );
+}
+
+#[test]
+fn test_json_explain() {
+    let explain = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_interpreter-starter-rust"))
+            .arg("explain")
+            .args(args)
+            .arg("--format=json")
+            .output()
+            .unwrap();
+
+        (String::from_utf8(output.stdout).unwrap(), output.status.code().unwrap())
+    };
+
+    assert_eq!(
+        explain(&["top-level-return"]),
+        (
+            "{\"kind\":\"code\",\"id\":\"L0203\",\"name\":\"top-level-return\",\"phase\":\"resolver\",\"explanation\":\"`return` appeared outside of any function or method.\\n\\n    return 1;\",\"severity\":\"error\"}\n"
+                .to_string(),
+            0
+        )
+    );
+
+    let (listing, exit_code) = explain(&[]);
+    assert_eq!(exit_code, 0);
+    assert!(listing.lines().all(|line| line.starts_with("{\"kind\":\"code\",\"id\":\"L0")));
+    assert!(listing.starts_with("{\"kind\":\"code\",\"id\":\"L0001\",\"name\":\"unexpected-character\",\"phase\":\"lexer\""));
+}
+
+#[test]
+fn test_json_repl() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_interpreter-starter-rust"))
+        .args(["repl", "--format=json"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let input = "var a = 1;\nprint a;\na + 1\n:tokens ;\nprint x;\n:bogus\n";
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "\
+{\"kind\":\"output\",\"text\":\"1\"}
+{\"kind\":\"value\",\"text\":\"2\"}
+{\"kind\":\"output\",\"text\":\"SEMICOLON ; null\"}
+{\"kind\":\"output\",\"text\":\"EOF  null\"}
+{\"kind\":\"error\",\"phase\":\"runtime\",\"message\":\"Undefined variable 'x'.\",\"line\":1,\"column\":7,\"span\":{\"start\":{\"line\":1,\"column\":7,\"offset\":6},\"end\":{\"line\":1,\"column\":8,\"offset\":7}},\"code\":\"L0304\",\"severity\":\"error\",\"label\":null,\"secondary\":[],\"notes\":[],\"help\":\"declare it with `var` before using it\"}
+"
+    );
+    // Meta-commands that can't run are command line problems, and the
+    // session goes on.
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "Unknown command :bogus, see :help.\n");
+    assert!(output.status.success());
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use interpreter_starter_rust::bytecode::{compile, Vm};
+use interpreter_starter_rust::interpret::{Interpreter, NativeFunction, Value};
+use interpreter_starter_rust::parse::parse;
+use interpreter_starter_rust::resolve::resolve;
+use interpreter_starter_rust::tokenize::tokenize;
+
+use common::SharedOutput;
+use pretty_assertions::assert_eq;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn run_with(interpreter: &mut Interpreter, file_contents: &str) -> Result<(), String> {
+    let (tokens, _) = tokenize(file_contents);
+    let statements = parse(tokens).unwrap();
+    interpreter.resolve(resolve(&statements).unwrap());
+
+    interpreter
+        .interpret(&statements)
+        .map_err(|runtime_error| runtime_error.to_string())
+}
+
+#[test]
+fn test_embedder_registers_natives() {
+    let output = SharedOutput::new();
+    let mut interpreter = Interpreter::with_output(Box::new(output.clone()));
+
+    interpreter.define_native(NativeFunction::new("greet", 1, |arguments| {
+        Ok(Value::String(format!("hello, {}", arguments[0]).into()))
+    }));
+
+    // Natives can capture host state.
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let log = calls.clone();
+    interpreter.define_native(NativeFunction::new("log", 1, move |arguments| {
+        log.borrow_mut().push(arguments[0].to_string());
+        Ok(Value::Nil)
+    }));
+
+    let result = run_with(&mut interpreter, "print greet(\"lox\"); log(1 + 2); print log;");
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(output.contents(), "hello, lox\n<native fn>\n");
+    assert_eq!(*calls.borrow(), vec!["3".to_string()]);
+}
+
+#[test]
+fn test_native_errors_are_runtime_errors() {
+    let mut interpreter = Interpreter::with_output(Box::new(std::io::sink()));
+
+    interpreter.define_native(NativeFunction::new("fail", 0, |_| Err("Native failure.".to_string())));
+
+    assert_eq!(run_with(&mut interpreter, "\nfail();"), Err("Native failure.\n[line 2]".to_string()));
+    assert_eq!(
+        run_with(&mut interpreter, "fail(1);"),
+        Err("Expected 0 arguments but got 1.\n[line 1]".to_string())
+    );
+}
+
+#[test]
+fn test_vm_shares_the_native_registry() {
+    let output = SharedOutput::new();
+    let mut vm = Vm::with_output(Box::new(output.clone()));
+
+    vm.define_native(NativeFunction::new("greet", 1, |arguments| {
+        Ok(Value::String(format!("hello, {}", arguments[0]).into()))
+    }));
+    vm.define_native(NativeFunction::new("fail", 0, |_| Err("Native failure.".to_string())));
+
+    let mut run = |file_contents: &str| {
+        let (tokens, _) = tokenize(file_contents);
+        let function = compile(tokens, vm.heap_mut()).unwrap();
+
+        vm.interpret(function).map_err(|runtime_error| runtime_error.to_string())
+    };
+
+    assert_eq!(run("print greet(\"vm\"); print clock() > 0; print greet;"), Ok(()));
+    assert_eq!(output.contents(), "hello, vm\ntrue\n<native fn>\n");
+
+    assert_eq!(run("\nfail();"), Err("Native failure.\n[line 2]".to_string()));
+    assert_eq!(
+        run("class A {}\ngreet(A);"),
+        Err("Native functions only take and return nil, booleans, numbers, strings and natives.\n[line 2]".to_string())
+    );
+}
@@ -0,0 +1,77 @@
+use pretty_assertions::assert_eq;
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Runs the binary with `args`, feeding `input` on stdin, and returns its
+/// stdout and stderr.
+fn run_repl(args: &[&str], input: &str) -> (String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_interpreter-starter-rust"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn test_repl_is_the_default() {
+    let input = "var a = 2;\nfun twice(x) {\n  return x * a;\n}\ntwice(21)\nprint @;\nprint \"still here\";\n";
+
+    // Without a terminal there is no prompt.
+    let (stdout, stderr) = run_repl(&[], input);
+
+    assert_eq!(stdout, "42\nstill here\n");
+    assert_eq!(stderr, "[line 1] Error: Unexpected character: @\n");
+    assert_eq!(run_repl(&["repl"], input), (stdout, stderr));
+}
+
+#[test]
+fn test_repl_meta_commands() {
+    let input = ":ast 1 + 2\nvar a = 1;\n:env\n:quit\nprint a;\n";
+
+    assert_eq!(
+        run_repl(&[], input),
+        ("(+ 1.0 2.0)\na = 1\nclock = <native fn>\n".to_string(), String::new())
+    );
+}
+
+#[test]
+fn test_repl_pretty_diagnostics() {
+    let input = "fun f() {\n  return 1 +;\n}\nprint x;\n:bogus\n";
+
+    assert_eq!(
+        run_repl(&["--diagnostics=pretty"], input),
+        (
+            String::new(),
+            "\
+error[L0101]: Expect expression.
+ --> <repl>:2:13
+  |
+2 |   return 1 +;
+  |             ^ found `;`
+
+error[L0304]: Undefined variable 'x'.
+ --> <repl>:1:7
+  |
+1 | print x;
+  |       ^
+  |
+  = help: declare it with `var` before using it
+
+Unknown command :bogus, see :help.
+"
+            .to_string()
+        )
+    );
+}
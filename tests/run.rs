@@ -0,0 +1,122 @@
+//! Runs every program under `tests/lox` and checks it against the
+//! expectations written in its comments:
+//!
+//! - `// expect: <line>`: a line the program prints to stdout.
+//! - `// expect error: <line>`: a compile error on stderr, exit code 65.
+//! - `// expect runtime error: <message>`: a runtime error raised on the
+//!   line of the comment, exit code 70.
+//!
+//! Both backends, the tree-walker and the bytecode VM, must agree on all of
+//! them, and so must the VM when it collects garbage on every allocation.
+
+mod common;
+
+use pretty_assertions::assert_eq;
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Debug, PartialEq)]
+struct Outcome {
+    stdout: Vec<String>,
+    stderr: Vec<String>,
+    exit_code: i32,
+}
+
+fn expected_outcome(source: &str) -> Outcome {
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let mut exit_code = 0;
+
+    for (index, line) in source.lines().enumerate() {
+        if let Some((_, expected)) = line.split_once("// expect: ") {
+            stdout.push(expected.to_string());
+        } else if let Some((_, expected)) = line.split_once("// expect error: ") {
+            stderr.push(expected.to_string());
+            exit_code = 65;
+        } else if let Some((_, expected)) = line.split_once("// expect runtime error: ") {
+            stderr.push(expected.to_string());
+            stderr.push(format!("[line {}]", index + 1));
+            exit_code = 70;
+        }
+    }
+
+    // A program that fails to compile prints nothing at all.
+    if exit_code == 65 {
+        stdout.clear();
+    }
+
+    Outcome {
+        stdout,
+        stderr,
+        exit_code,
+    }
+}
+
+fn actual_outcome(args: &[&str], path: &Path) -> Outcome {
+    let output = Command::new(env!("CARGO_BIN_EXE_interpreter-starter-rust"))
+        .args(args)
+        .arg(path)
+        .output()
+        .unwrap();
+
+    let lines = |bytes: &[u8]| {
+        String::from_utf8_lossy(bytes)
+            .lines()
+            .map(|line| line.to_string())
+            .collect::<Vec<String>>()
+    };
+
+    Outcome {
+        stdout: lines(&output.stdout),
+        stderr: lines(&output.stderr),
+        exit_code: output.status.code().unwrap(),
+    }
+}
+
+fn run_corpus(args: &[&str]) {
+    let files = common::lox_files(&common::tests_dir().join("lox"));
+    assert!(!files.is_empty());
+
+    for path in files {
+        let source = fs::read_to_string(&path).unwrap();
+
+        assert_eq!(
+            actual_outcome(args, &path),
+            expected_outcome(&source),
+            "{}",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn test_run_corpus() {
+    run_corpus(&["run"]);
+}
+
+#[test]
+fn test_run_corpus_on_vm() {
+    run_corpus(&["run", "--backend=vm"]);
+}
+
+#[test]
+fn test_run_corpus_on_vm_with_gc_stress() {
+    run_corpus(&["run", "--backend=vm", "--gc-stress"]);
+}
+
+#[test]
+fn test_gc_log_reports_collections() {
+    let path = common::tests_dir().join("lox/gc/survivors.lox");
+    let source = fs::read_to_string(&path).unwrap();
+
+    let outcome = actual_outcome(&["run", "--backend=vm", "--gc-stress", "--gc-log"], &path);
+
+    assert_eq!(outcome.stdout, expected_outcome(&source).stdout);
+    assert_eq!(outcome.exit_code, 0);
+    assert!(!outcome.stderr.is_empty());
+    for line in &outcome.stderr {
+        assert!(line.starts_with("-- gc collected "), "{}", line);
+    }
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use interpreter_starter_rust::tokenize::{tokenize, Lexer, LiteralType, Token, TokenType};
+
+use pretty_assertions::assert_eq;
+
+use std::fs;
+
+fn read_lox(name: &str) -> String {
+    fs::read_to_string(common::tests_dir().join(name)).unwrap()
+}
+
+#[test]
+fn test_public_token_accessors() {
+    let (tokens, token_errors) = tokenize("var x = \"hi\";\nx");
+
+    let token = &tokens[3];
+    assert_eq!(token.token_type(), &TokenType::String);
+    assert_eq!(token.lexeme(), "\"hi\"");
+    assert_eq!(token.literal(), Some(&LiteralType::String("hi".into())));
+    assert_eq!(token.line(), 1);
+
+    let token = &tokens[5];
+    assert_eq!(token.token_type(), &TokenType::Identifier);
+    assert_eq!(token.literal(), None);
+    assert_eq!(token.line(), 2);
+
+    assert!(token_errors.is_empty());
+}
+
+#[test]
+fn test_tokenize_number_file() {
+    let source = read_lox("number.lox");
+    let (tokens, token_errors) = tokenize(&source);
+
+    let expected_tokens = vec![
+        Token::new_number(TokenType::Number, "1234.1234", Some(1234.1234), 1),
+        Token::new(TokenType::Eof, "", None, 2),
+    ];
+
+    assert_eq!(tokens, expected_tokens);
+    assert!(token_errors.is_empty());
+}
+
+#[test]
+fn test_tokenize_unexpected_chars_file() {
+    let source = read_lox("unexpected_chars.lox");
+    let (tokens, token_errors) = tokenize(&source);
+
+    let tokens_msg = tokens
+        .iter()
+        .map(|token| token.to_string())
+        .collect::<Vec<String>>();
+    let token_errors_msg = token_errors
+        .iter()
+        .map(|token_error| token_error.to_string())
+        .collect::<Vec<String>>();
+
+    assert_eq!(
+        tokens_msg,
+        vec![
+            "COMMA , null",
+            "DOT . null",
+            "ERROR $ null",
+            "LEFT_PAREN ( null",
+            "ERROR # null",
+            "EOF  null",
+        ]
+    );
+    assert_eq!(
+        token_errors_msg,
+        vec![
+            "[line 1] Error: Unexpected character: $",
+            "[line 1] Error: Unexpected character: #",
+        ]
+    );
+    assert_eq!(token_errors[1].message(), "Unexpected character: #");
+}
+
+#[test]
+fn test_lexer_borrows_lexemes_from_source() {
+    let source = read_lox("test.lox");
+    let source_range = source.as_bytes().as_ptr_range();
+
+    let lexemes = Lexer::new(&source)
+        .map(|result| result.unwrap())
+        .take_while(|token| token.token_type() != &TokenType::Eof)
+        .map(|token| token.lexeme())
+        .collect::<Vec<&str>>();
+
+    assert_eq!(lexemes, vec!["(", "(", ")"]);
+    for lexeme in lexemes {
+        assert!(source_range.contains(&lexeme.as_ptr()));
+    }
+}
+
+#[test]
+fn test_trivia_round_trips_every_lox_file() {
+    let lox_files = common::lox_files(&common::tests_dir());
+    assert!(!lox_files.is_empty());
+
+    for path in lox_files {
+        let source = fs::read_to_string(&path).unwrap();
+        let round_trip = Lexer::new(&source)
+            .with_trivia()
+            .filter_map(|result| result.ok())
+            .map(|token| {
+                format!(
+                    "{}{}{}",
+                    token.leading_trivia(),
+                    token.lexeme(),
+                    token.trailing_trivia()
+                )
+            })
+            .collect::<String>();
+
+        assert_eq!(round_trip, source, "{}", path.display());
+    }
+}